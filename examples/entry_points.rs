@@ -0,0 +1,140 @@
+use bevy::{
+    asset::{embedded_asset, RenderAssetUsages},
+    prelude::*,
+};
+use bevy_render::{
+    render_resource::{
+        AsBindGroup, BindGroupLayout, CommandEncoder, ComputePassDescriptor,
+        ComputePipelineDescriptor, SpecializedComputePipeline,
+    },
+    renderer::RenderDevice,
+    storage::ShaderStorageBuffer,
+};
+
+use gigs::*;
+use input::{JobAsBindGroup, JobComputePipeline, JobInputItem};
+
+const VALUE_COUNT: u32 = 1024;
+const WORKGROUP_SIZE: u32 = 64;
+
+fn main() -> AppExit {
+    let mut app = App::new();
+
+    app.add_plugins((DefaultPlugins, GraphicsJobsPlugin::default()))
+        .init_graphics_job::<BufferOpJob>();
+
+    embedded_asset!(app, "examples", "entry_points.wgsl");
+
+    app.add_systems(Startup, setup);
+
+    app.run()
+}
+
+fn setup(mut storage_buffers: ResMut<Assets<ShaderStorageBuffer>>, mut commands: Commands) {
+    let data = vec![0u8; VALUE_COUNT as usize * size_of::<f32>()];
+    let values = storage_buffers.add(ShaderStorageBuffer::new(
+        &data[..],
+        RenderAssetUsages::all(),
+    ));
+
+    // Both jobs use the same pipeline resource and shader, but select
+    // different entry points through their pipeline keys.
+    let double_job = BufferOpJob {
+        values: values.clone(),
+    };
+
+    commands
+        .spawn((
+            BufferOpJob { values },
+            JobComputePipeline::<BufferOpPipeline>(BufferOp::Fill),
+        ))
+        .observe(
+            move |_trigger: Trigger<JobComplete>, mut commands: Commands| {
+                println!("Filled buffer!");
+                commands
+                    .spawn((
+                        double_job.clone(),
+                        JobComputePipeline::<BufferOpPipeline>(BufferOp::Double),
+                    ))
+                    .observe(|_trigger: Trigger<JobComplete>| println!("Doubled buffer!"));
+            },
+        );
+}
+
+/// Selects which entry point of `entry_points.wgsl` a job will run
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum BufferOp {
+    Fill,
+    Double,
+}
+
+impl BufferOp {
+    fn entry_point(&self) -> &'static str {
+        match self {
+            BufferOp::Fill => "fill",
+            BufferOp::Double => "double",
+        }
+    }
+}
+
+#[derive(AsBindGroup, Clone, Component)]
+struct BufferOpJob {
+    #[storage(0, visibility(compute))]
+    values: Handle<ShaderStorageBuffer>,
+}
+
+#[derive(Resource)]
+struct BufferOpPipeline {
+    layout: BindGroupLayout,
+    shader: Handle<Shader>,
+}
+
+impl FromWorld for BufferOpPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = BufferOpJob::bind_group_layout(world.resource::<RenderDevice>());
+        let shader = world
+            .resource::<AssetServer>()
+            .load("embedded://entry_points/entry_points.wgsl");
+
+        Self { layout, shader }
+    }
+}
+
+impl SpecializedComputePipeline for BufferOpPipeline {
+    type Key = BufferOp;
+
+    fn specialize(&self, op: Self::Key) -> ComputePipelineDescriptor {
+        ComputePipelineDescriptor {
+            label: Some(format!("buffer_op_{}", op.entry_point()).into()),
+            layout: vec![self.layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: self.shader.clone(),
+            shader_defs: Vec::new(),
+            entry_point: op.entry_point().into(),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+impl GraphicsJob for BufferOpJob {
+    type In = (JobAsBindGroup, JobComputePipeline<BufferOpPipeline>);
+
+    fn run(
+        &self,
+        _world: &World,
+        _render_device: &RenderDevice,
+        command_encoder: &mut CommandEncoder,
+        (job_bind_group, job_pipeline): JobInputItem<Self, Self::In>,
+    ) -> Result<(), JobError> {
+        let mut compute_pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("buffer_op_compute_pass"),
+            timestamp_writes: None,
+        });
+
+        compute_pass.set_bind_group(0, &job_bind_group.bind_group, &[]);
+        compute_pass.set_pipeline(job_pipeline);
+        compute_pass.dispatch_workgroups(VALUE_COUNT.div_ceil(WORKGROUP_SIZE), 1, 1);
+
+        Ok(())
+    }
+}
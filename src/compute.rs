@@ -0,0 +1,51 @@
+//! A simpler interface for graphics jobs that run a single compute shader.
+
+use bevy_ecs::{component::Component, world::World};
+use bevy_render::{
+    render_resource::{AsBindGroup, CommandEncoder, ComputePass, ComputePassDescriptor},
+    renderer::RenderDevice,
+};
+
+use crate::{
+    input::{JobAsBindGroup, JobComputePipeline, JobInputItem, SpecializedJobComputePipeline},
+    GraphicsJob, JobError,
+};
+
+/// A trait for jobs that dispatch a single compute shader, using the job itself
+/// as a bind group.
+///
+/// Implementing this trait implements [`GraphicsJob`] as well. When the job runs,
+/// a [`ComputePass`] is begun with the job's bind group set at index `0` and the
+/// pipeline already set, so [`dispatch`](ComputeJob::dispatch) only needs to call
+/// `dispatch_workgroups` (or similar). Jobs must be spawned with a [`JobComputePipeline`]
+/// component selecting the pipeline variant to use.
+///
+/// For anything more involved, such as multiple passes or render passes, implement
+/// [`GraphicsJob`] directly.
+pub trait ComputeJob: Component + Clone + AsBindGroup {
+    type Pipeline: SpecializedJobComputePipeline;
+
+    fn dispatch(&self, world: &World, compute_pass: &mut ComputePass<'_>) -> Result<(), JobError>;
+}
+
+impl<J: ComputeJob> GraphicsJob for J {
+    type In = (JobAsBindGroup, JobComputePipeline<J::Pipeline>);
+
+    fn run(
+        &self,
+        world: &World,
+        _render_device: &RenderDevice,
+        command_encoder: &mut CommandEncoder,
+        (bind_group, pipeline): JobInputItem<Self, Self::In>,
+    ) -> Result<(), JobError> {
+        let mut compute_pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some(<Self as GraphicsJob>::label().original()),
+            timestamp_writes: None,
+        });
+
+        compute_pass.set_bind_group(0, &bind_group.bind_group, &[]);
+        compute_pass.set_pipeline(pipeline);
+
+        self.dispatch(world, &mut compute_pass)
+    }
+}
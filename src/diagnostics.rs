@@ -0,0 +1,155 @@
+//! Integration of job metrics with Bevy's diagnostics. Requires the `diagnostics` feature.
+
+use bevy_app::{App, Plugin, Update};
+use bevy_diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy_ecs::{
+    schedule::IntoSystemConfigs,
+    system::{Query, Res, Resource},
+};
+use bevy_time::{Real, Time};
+use crossbeam_channel::Receiver;
+
+use crate::{
+    meta::JobStatus,
+    runner::{sync_completed_jobs_main_world, sync_job_queue_stats_main_world},
+    JobCompletion, JobError, JobQueueStats, JobSinks,
+};
+
+/// The number of jobs completed successfully per second
+pub const JOBS_COMPLETED: DiagnosticPath = DiagnosticPath::const_new("gigs/jobs_completed");
+/// The number of jobs failed per second, for any reason
+pub const JOBS_FAILED: DiagnosticPath = DiagnosticPath::const_new("gigs/jobs_failed");
+/// The number of jobs failed with [`JobError::TimedOut`] per second
+pub const JOBS_TIMED_OUT: DiagnosticPath = DiagnosticPath::const_new("gigs/jobs_failed/timed_out");
+/// The number of jobs failed with [`JobError::InputsFailed`] per second
+pub const JOBS_INPUTS_FAILED: DiagnosticPath =
+    DiagnosticPath::const_new("gigs/jobs_failed/inputs_failed");
+/// The number of jobs failed with [`JobError::ExecutionFailed`] per second
+pub const JOBS_EXECUTION_FAILED: DiagnosticPath =
+    DiagnosticPath::const_new("gigs/jobs_failed/execution_failed");
+/// The number of jobs failed with [`JobError::Cancelled`] per second
+pub const JOBS_CANCELLED: DiagnosticPath = DiagnosticPath::const_new("gigs/jobs_failed/cancelled");
+/// The number of jobs failed with [`JobError::DependencyFailed`] per second
+pub const JOBS_DEPENDENCY_FAILED: DiagnosticPath =
+    DiagnosticPath::const_new("gigs/jobs_failed/dependency_failed");
+/// The number of jobs failed with [`JobError::NoRenderDevice`] per second
+pub const JOBS_NO_RENDER_DEVICE: DiagnosticPath =
+    DiagnosticPath::const_new("gigs/jobs_failed/no_render_device");
+/// The average number of frames that pending jobs have stalled for, waiting on their
+/// inputs or for room to execute, as in [`JobStatus`]. `0` when no jobs are pending.
+pub const JOBS_FRAMES_STALLED: DiagnosticPath =
+    DiagnosticPath::const_new("gigs/jobs_frames_stalled");
+/// The number of jobs executed each frame, as in [`JobQueueStats::executed_last_frame`]
+pub const JOBS_EXECUTED: DiagnosticPath = DiagnosticPath::const_new("gigs/jobs_executed");
+
+const FAILED_PATH_COUNT: usize = 6;
+
+const FAILED_PATHS: [DiagnosticPath; FAILED_PATH_COUNT] = [
+    JOBS_TIMED_OUT,
+    JOBS_INPUTS_FAILED,
+    JOBS_EXECUTION_FAILED,
+    JOBS_CANCELLED,
+    JOBS_DEPENDENCY_FAILED,
+    JOBS_NO_RENDER_DEVICE,
+];
+
+/// Returns the index of the diagnostic path in `FAILED_PATHS` for a kind of error
+fn failed_path_index(error: &JobError) -> usize {
+    match error {
+        JobError::TimedOut => 0,
+        JobError::InputsFailed => 1,
+        JobError::ExecutionFailed(_) => 2,
+        JobError::Cancelled => 3,
+        JobError::DependencyFailed => 4,
+        JobError::NoRenderDevice => 5,
+    }
+}
+
+/// A plugin that registers diagnostics for graphics jobs with Bevy's
+/// [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore), updated each frame. See
+/// the constants in this module for the available paths. The diagnostics can be
+/// displayed with any of Bevy's diagnostics plugins, like `LogDiagnosticsPlugin`.
+///
+/// Completions are read through a channel registered with [`JobSinks`], so they're
+/// counted even when [`JobSinks::trigger_observers`] is disabled. Must be added after
+/// [`GraphicsJobsPlugin`](crate::GraphicsJobsPlugin).
+pub struct JobDiagnosticsPlugin;
+
+#[derive(Resource)]
+struct JobDiagnosticsReceiver(Receiver<JobCompletion>);
+
+impl Plugin for JobDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        app.world_mut()
+            .get_resource_or_insert_with(JobSinks::default)
+            .add(sender);
+
+        app.register_diagnostic(Diagnostic::new(JOBS_COMPLETED).with_suffix("/s"))
+            .register_diagnostic(Diagnostic::new(JOBS_FAILED).with_suffix("/s"))
+            .register_diagnostic(Diagnostic::new(JOBS_FRAMES_STALLED))
+            .register_diagnostic(Diagnostic::new(JOBS_EXECUTED));
+        for path in FAILED_PATHS {
+            app.register_diagnostic(Diagnostic::new(path).with_suffix("/s"));
+        }
+
+        app.insert_resource(JobDiagnosticsReceiver(receiver))
+            .add_systems(
+                Update,
+                update_job_diagnostics
+                    .after(sync_completed_jobs_main_world)
+                    .after(sync_job_queue_stats_main_world),
+            );
+    }
+}
+
+fn update_job_diagnostics(
+    receiver: Res<JobDiagnosticsReceiver>,
+    stats: Res<JobQueueStats>,
+    job_statuses: Query<&JobStatus>,
+    time: Res<Time<Real>>,
+    mut diagnostics: Diagnostics,
+) {
+    let mut completed = 0u32;
+    let mut failed = [0u32; FAILED_PATH_COUNT];
+    for completion in receiver.0.try_iter() {
+        match &completion.result {
+            Ok(()) => completed += 1,
+            Err(error) => failed[failed_path_index(error)] += 1,
+        }
+    }
+
+    let delta = time.delta_secs_f64();
+    let per_second = |count: u32| {
+        if delta > 0.0 {
+            count as f64 / delta
+        } else {
+            0.0
+        }
+    };
+
+    diagnostics.add_measurement(&JOBS_COMPLETED, || per_second(completed));
+    diagnostics.add_measurement(&JOBS_FAILED, || per_second(failed.iter().sum()));
+    for (path, count) in FAILED_PATHS.iter().zip(failed) {
+        diagnostics.add_measurement(path, || per_second(count));
+    }
+    diagnostics.add_measurement(&JOBS_FRAMES_STALLED, || {
+        let (count, total) = job_statuses
+            .iter()
+            .filter_map(|status| match status {
+                JobStatus::Waiting { frames_stalled } | JobStatus::Ready { frames_stalled } => {
+                    Some(*frames_stalled)
+                }
+                JobStatus::Executing => None,
+            })
+            .fold((0u32, 0u64), |(count, total), frames| {
+                (count + 1, total + frames as u64)
+            });
+        if count > 0 {
+            total as f64 / count as f64
+        } else {
+            0.0
+        }
+    });
+    diagnostics.add_measurement(&JOBS_EXECUTED, || stats.executed_last_frame as f64);
+}
@@ -1,14 +1,273 @@
+use core::{any::TypeId, future::Future};
+
 use bevy_app::App;
+use bevy_ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    system::{Commands, EntityCommands, IntoObserverSystem, Resource},
+    world::World,
+};
+use bevy_utils::{tracing::warn, HashSet};
+
+use crossbeam_channel::Sender;
 
-use super::{GraphicsJob, SpecializedGraphicsJobPlugin};
+use super::{
+    meta::{CancelJob, ExtractJobComponentPlugin, JobPriority},
+    runner::JobWaiters,
+    DisabledJobTypes, GraphicsJob, JobComplete, JobCompletion, JobError, JobSinks, JobTypeSettings,
+    SpecializedGraphicsJobPlugin,
+};
 
-/// An extension trait for initializing graphics jobs on [`App`]
+/// The types of graphics jobs that have been initialized on the app
+#[derive(Resource, Default)]
+struct InitializedJobTypes(HashSet<TypeId>);
+
+/// An extension trait for initializing graphics jobs on [`App`]. Initializing the
+/// same type of job more than once has no effect, besides logging a warning.
 pub trait InitGraphicsJobExt {
-    fn init_graphics_job<J: GraphicsJob>(&mut self) -> &mut Self;
+    fn init_graphics_job<J: GraphicsJob + Clone>(&mut self) -> &mut Self;
+
+    /// Initializes a graphics job with custom settings for that job type. These
+    /// override the global [`JobExecutionSettings`](crate::JobExecutionSettings)
+    /// for jobs of this type, where set.
+    fn init_graphics_job_with<J: GraphicsJob + Clone>(
+        &mut self,
+        settings: JobTypeSettings,
+    ) -> &mut Self;
+
+    /// Initializes a graphics job that is moved into the render world rather than
+    /// cloned, for jobs that don't implement [`Clone`] or are expensive to clone.
+    ///
+    /// Note: the job component is removed from the main-world entity once the job
+    /// is extracted, so it can't be queried in the main world afterwards.
+    fn init_moved_graphics_job<J: GraphicsJob>(&mut self, settings: JobTypeSettings) -> &mut Self;
+
+    /// Extracts a component on graphics job entities to the render world, so
+    /// it can be used as a job input with the `&T` [`JobInput`](crate::input::JobInput)
+    /// impl. Components are re-extracted whenever they change. Calling this
+    /// more than once for the same component has no effect.
+    fn extract_job_component<T: Component + Clone>(&mut self) -> &mut Self;
+
+    /// Disables a graphics job type, cancelling all of its jobs. See [`DisabledJobTypes`]
+    /// for the limitations of this, and for disabling job types from systems.
+    fn disable_graphics_job<J: GraphicsJob>(&mut self) -> &mut Self;
 }
 
 impl InitGraphicsJobExt for App {
-    fn init_graphics_job<J: GraphicsJob>(&mut self) -> &mut Self {
-        self.add_plugins(SpecializedGraphicsJobPlugin::<J>::default())
+    fn init_graphics_job<J: GraphicsJob + Clone>(&mut self) -> &mut Self {
+        self.init_graphics_job_with::<J>(JobTypeSettings::default())
+    }
+
+    fn init_graphics_job_with<J: GraphicsJob + Clone>(
+        &mut self,
+        settings: JobTypeSettings,
+    ) -> &mut Self {
+        add_job_plugin(self, SpecializedGraphicsJobPlugin::<J>::new(settings))
+    }
+
+    fn init_moved_graphics_job<J: GraphicsJob>(&mut self, settings: JobTypeSettings) -> &mut Self {
+        add_job_plugin(self, SpecializedGraphicsJobPlugin::<J>::moved(settings))
+    }
+
+    fn extract_job_component<T: Component + Clone>(&mut self) -> &mut Self {
+        if !self.is_plugin_added::<ExtractJobComponentPlugin<T>>() {
+            self.add_plugins(ExtractJobComponentPlugin::<T>::default());
+        }
+        self
+    }
+
+    fn disable_graphics_job<J: GraphicsJob>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(DisabledJobTypes::default)
+            .disable::<J>();
+        self
+    }
+}
+
+fn add_job_plugin<J: GraphicsJob>(
+    app: &mut App,
+    plugin: SpecializedGraphicsJobPlugin<J>,
+) -> &mut App {
+    let newly_initialized = app
+        .world_mut()
+        .get_resource_or_insert_with(InitializedJobTypes::default)
+        .0
+        .insert(TypeId::of::<J>());
+
+    if !newly_initialized {
+        warn!(
+            "`init_graphics_job` was called more than once for `{}`. Only the first call has any effect.",
+            J::label()
+        );
+        return app;
+    }
+
+    app.add_plugins(plugin)
+}
+
+/// An extension trait for registering [`JobSinks`] on [`App`]
+pub trait JobSinkExt {
+    /// Registers a channel that the result of every completed job will be sent to.
+    /// [`GraphicsJobsPlugin`](crate::GraphicsJobsPlugin) must be added first.
+    fn register_job_sink(&mut self, sender: Sender<JobCompletion>) -> &mut Self;
+}
+
+impl JobSinkExt for App {
+    fn register_job_sink(&mut self, sender: Sender<JobCompletion>) -> &mut Self {
+        self.world_mut().resource_mut::<JobSinks>().add(sender);
+        self
+    }
+}
+
+/// An extension trait for awaiting the completion of graphics jobs from async code.
+pub trait AwaitJobsExt {
+    /// Returns a future that resolves once every listed job has completed, with
+    /// the result of each job in the order given.
+    ///
+    /// Jobs that have already completed resolve with their result for as long as the
+    /// job entity exists, which may be only until the end of the frame. If a job entity
+    /// doesn't exist, or the app exits before the job completes, its result will be
+    /// [`JobError::Cancelled`].
+    fn await_jobs(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> impl Future<Output = Vec<Result<(), JobError>>> + Send + 'static;
+}
+
+impl AwaitJobsExt for Commands<'_, '_> {
+    fn await_jobs(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> impl Future<Output = Vec<Result<(), JobError>>> + Send + 'static {
+        let (senders, receivers): (Vec<_>, Vec<_>) = entities
+            .into_iter()
+            .map(|entity| {
+                let (sender, receiver) = async_channel::bounded(1);
+                ((entity, sender), receiver)
+            })
+            .unzip();
+
+        self.queue(move |world: &mut World| {
+            for (entity, sender) in senders {
+                JobWaiters::add(world, entity, sender);
+            }
+        });
+
+        async move {
+            let mut results = Vec::with_capacity(receivers.len());
+            for receiver in receivers {
+                results.push(receiver.recv().await.unwrap_or(Err(JobError::Cancelled)));
+            }
+            results
+        }
+    }
+}
+
+/// A handle to a job spawned with [`spawn_job_async`](SpawnJobAsyncExt::spawn_job_async),
+/// for awaiting its completion. Dropping the handle doesn't cancel the job.
+pub struct JobHandle {
+    entity: Entity,
+    receiver: async_channel::Receiver<Result<(), JobError>>,
+}
+
+impl JobHandle {
+    /// The main-world job entity
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Waits for the job to complete, returning its result. If the job entity is
+    /// despawned before the job completes, or the app exits first, the result will
+    /// be [`JobError::Cancelled`].
+    pub async fn wait(self) -> Result<(), JobError> {
+        self.receiver
+            .recv()
+            .await
+            .unwrap_or(Err(JobError::Cancelled))
+    }
+}
+
+/// An extension trait for spawning graphics jobs that are awaited from async code,
+/// for example from a task on the `AsyncComputeTaskPool`.
+pub trait SpawnJobAsyncExt {
+    /// Spawns a job, returning a [`JobHandle`] to await its completion with.
+    fn spawn_job_async(&mut self, bundle: impl Bundle) -> JobHandle;
+}
+
+impl SpawnJobAsyncExt for Commands<'_, '_> {
+    fn spawn_job_async(&mut self, bundle: impl Bundle) -> JobHandle {
+        let entity = self.spawn(bundle).id();
+        let (sender, receiver) = async_channel::bounded(1);
+        self.queue(move |world: &mut World| {
+            JobWaiters::add(world, entity, sender);
+        });
+        JobHandle { entity, receiver }
+    }
+}
+
+impl SpawnJobAsyncExt for World {
+    fn spawn_job_async(&mut self, bundle: impl Bundle) -> JobHandle {
+        let entity = self.spawn(bundle).id();
+        let (sender, receiver) = async_channel::bounded(1);
+        JobWaiters::add(self, entity, sender);
+        JobHandle { entity, receiver }
+    }
+}
+
+/// An extension trait for spawning graphics jobs from [`Commands`].
+pub trait SpawnGraphicsJobExt {
+    /// Spawns a job with the given priority, returning its [`EntityCommands`] so that
+    /// other components, such as job inputs, may be inserted.
+    fn spawn_graphics_job<J: GraphicsJob>(
+        &mut self,
+        job: J,
+        priority: JobPriority,
+    ) -> EntityCommands<'_>;
+
+    /// Spawns a job with the given priority, and an observer of [`JobComplete`] on
+    /// the job entity, which runs once the job completes or fails.
+    fn spawn_graphics_job_with<J: GraphicsJob, M>(
+        &mut self,
+        job: J,
+        priority: JobPriority,
+        on_complete: impl IntoObserverSystem<JobComplete, (), M>,
+    ) -> EntityCommands<'_>;
+}
+
+impl SpawnGraphicsJobExt for Commands<'_, '_> {
+    fn spawn_graphics_job<J: GraphicsJob>(
+        &mut self,
+        job: J,
+        priority: JobPriority,
+    ) -> EntityCommands<'_> {
+        self.spawn((job, priority))
+    }
+
+    fn spawn_graphics_job_with<J: GraphicsJob, M>(
+        &mut self,
+        job: J,
+        priority: JobPriority,
+        on_complete: impl IntoObserverSystem<JobComplete, (), M>,
+    ) -> EntityCommands<'_> {
+        let mut entity = self.spawn((job, priority));
+        entity.observe(on_complete);
+        entity
+    }
+}
+
+/// An extension trait for cancelling graphics jobs from [`Commands`].
+pub trait CancelJobExt {
+    /// Cancels a scheduled job by adding [`CancelJob`] to it. This has no effect
+    /// if the job has already executed, or its entity has been despawned.
+    fn cancel_job(&mut self, entity: Entity) -> &mut Self;
+}
+
+impl CancelJobExt for Commands<'_, '_> {
+    fn cancel_job(&mut self, entity: Entity) -> &mut Self {
+        if let Some(mut entity) = self.get_entity(entity) {
+            entity.try_insert(CancelJob);
+        }
+        self
     }
 }
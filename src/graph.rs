@@ -0,0 +1,174 @@
+//! Running graphics jobs from within the render graph.
+
+use std::sync::Mutex;
+
+use bevy_ecs::{
+    entity::Entity,
+    query::{QueryState, With, Without},
+    world::{EntityRef, FromWorld, World},
+};
+use bevy_render::{
+    render_graph::{Node, NodeRunError, RenderGraphContext, RenderLabel},
+    render_resource::CommandEncoderDescriptor,
+    renderer::{RenderContext, RenderDevice},
+    sync_world::MainEntity,
+};
+use bevy_utils::HashSet;
+
+use crate::{
+    meta::{CancelJob, JobDisabled, JobPriority, JobTypePriorityOverrides, RunInGraph},
+    runner::{
+        run_job, scheduled_execution_order, send_job_result, send_job_started, AppFocus,
+        DynamicJob, JobDeferred, JobReady, JobResultMainWorldSender, JobResultSender,
+        ResumedGraphJobs,
+    },
+    JobExecutionSettings,
+};
+
+/// A render graph label for [`GraphicsJobNode`]
+#[derive(RenderLabel, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GraphicsJobLabel;
+
+type GraphJobQuery = (
+    EntityRef<'static>,
+    Option<&'static MainEntity>,
+    &'static DynamicJob,
+    &'static JobPriority,
+);
+
+type GraphJobFilter = (
+    With<JobReady>,
+    With<RunInGraph>,
+    Without<JobDisabled>,
+    Without<JobDeferred>,
+    Without<CancelJob>,
+);
+
+/// A render graph node that executes ready jobs with [`RunInGraph`], in order of
+/// priority, adding the command buffer of each successful job to the graph's commands
+/// with [`RenderContext::add_command_buffer`]. This allows
+/// jobs to run at a specific point in the render graph, for example to sample the
+/// depth buffer after the main opaque pass:
+///
+/// ```ignore
+/// render_app
+///     .add_render_graph_node::<GraphicsJobNode>(Core3d, GraphicsJobLabel)
+///     .add_render_graph_edges(
+///         Core3d,
+///         (Node3d::MainOpaquePass, GraphicsJobLabel, Node3d::MainTransmissivePass),
+///     );
+/// ```
+///
+/// Each job runs at most once per frame, even if the node is in a sub-graph that
+/// runs once per view. Only works when jobs run in the [`RenderApp`](bevy_render::RenderApp).
+///
+/// Note: jobs run from the graph aren't counted towards the per-frame limits in
+/// [`JobExecutionSettings`], aren't timed by [`JobTimestampsPlugin`](crate::timing::JobTimestampsPlugin),
+/// and only trigger [`JobStarted`](crate::JobStarted) in the main world. Jobs don't
+/// encode into the [`RenderContext`]'s own command encoder, since `wgpu` can't discard
+/// commands once they're encoded: each job encodes into its own command buffer
+/// instead, so the commands of failed jobs are discarded. The buffers still execute
+/// in order with the commands of the surrounding nodes.
+pub struct GraphicsJobNode {
+    jobs: QueryState<GraphJobQuery, GraphJobFilter>,
+    executed: Mutex<HashSet<Entity>>,
+}
+
+impl FromWorld for GraphicsJobNode {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            jobs: QueryState::new(world),
+            executed: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl Node for GraphicsJobNode {
+    fn update(&mut self, world: &mut World) {
+        self.jobs.update_archetypes(world);
+        self.executed.get_mut().unwrap().clear();
+    }
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let exec_settings = world.resource::<JobExecutionSettings>();
+        let render_device = world.resource::<RenderDevice>();
+        let priority_overrides = world.resource::<JobTypePriorityOverrides>();
+        let main_world_sender = world.resource::<JobResultMainWorldSender>();
+        let job_result_sender = world.resource::<JobResultSender>();
+        let paused = world.resource::<AppFocus>().is_paused(exec_settings);
+        let mut executed = self.executed.lock().unwrap();
+
+        let mut sorted_jobs = self
+            .jobs
+            .iter_manual(world)
+            .filter(|(entity_ref, ..)| !executed.contains(&entity_ref.id()))
+            .map(|(entity_ref, main_entity, job, priority)| {
+                let priority = priority_overrides.get(job.job_type()).unwrap_or(*priority);
+                (entity_ref, main_entity, job, priority)
+            })
+            .filter(|(.., priority)| !paused || priority.is_critical())
+            .collect::<Vec<_>>();
+        sorted_jobs.sort_by_key(|(entity_ref, .., priority)| {
+            scheduled_execution_order(*entity_ref, *priority, exec_settings.priority_aging_rate)
+        });
+
+        for (entity_ref, main_entity, job, _) in sorted_jobs {
+            executed.insert(entity_ref.id());
+            send_job_started(
+                entity_ref,
+                main_entity,
+                job,
+                exec_settings,
+                main_world_sender,
+            );
+
+            // each job encodes separately, so the commands of failed jobs are discarded
+            let encoder_label = job.encoder_label(entity_ref);
+            let mut command_encoder =
+                render_device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some(encoder_label.as_ref()),
+                });
+            if exec_settings.debug_markers {
+                command_encoder.push_debug_group(&encoder_label);
+            }
+            let result = run_job(
+                job,
+                entity_ref,
+                world,
+                render_device,
+                &mut command_encoder,
+                exec_settings.catch_panics,
+            );
+            if exec_settings.debug_markers {
+                command_encoder.pop_debug_group();
+            }
+
+            // deferred jobs stay scheduled, and run again next frame
+            let Some(result) = result.into_result() else {
+                continue;
+            };
+
+            if result.is_ok() {
+                render_context.add_command_buffer(command_encoder.finish());
+            }
+
+            if send_job_result(
+                entity_ref,
+                main_entity,
+                result,
+                exec_settings,
+                job_result_sender,
+            ) {
+                let resumed = world.resource::<ResumedGraphJobs>();
+                resumed.0.lock().unwrap().push(entity_ref.id());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,30 +1,55 @@
-use core::marker::PhantomData;
+use core::{
+    marker::PhantomData,
+    ops::Range,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    time::Duration,
+};
+use std::sync::Mutex;
 
 use bevy_app::{App, Plugin};
+use bevy_asset::{AssetServer, Assets, Handle, LoadState};
 use bevy_ecs::{
-    component::Component,
+    change_detection::DetectChanges,
+    component::{Component, ComponentId},
     entity::Entity,
-    query::{Changed, QueryItem, ReadOnlyQueryData, WorldQuery},
+    query::{Changed, Has, QueryItem, ReadOnlyQueryData, With, Without, WorldQuery},
     schedule::IntoSystemConfigs,
     system::{lifetimeless::Read, Commands, Query, Res, ResMut, Resource, StaticSystemParam},
-    world::{FromWorld, World},
+    world::{DeferredWorld, EntityRef, FromWorld, Ref, World},
 };
-use bevy_utils::all_tuples;
+use bevy_image::Image;
+use bevy_utils::{all_tuples, tracing::error, HashMap, Instant};
 
 use bevy_render::{
     extract_component::{ExtractComponent, ExtractComponentPlugin},
+    mesh::{allocator::MeshAllocator, Mesh, RenderMesh, RenderMeshBufferInfo},
+    render_asset::{RenderAsset, RenderAssets},
     render_resource::{
-        AsBindGroup, BindGroupLayout, CachedComputePipelineId, CachedPipelineState,
-        CachedRenderPipelineId, ComputePipeline, PipelineCache, PreparedBindGroup, RenderPipeline,
-        SpecializedComputePipeline, SpecializedComputePipelines, SpecializedRenderPipeline,
-        SpecializedRenderPipelines,
+        encase::{internal::WriteInto, StorageBuffer},
+        AsBindGroup, AsBindGroupError, BindGroupLayout, BindingResource, Buffer, BufferDescriptor,
+        BufferUsages, CachedComputePipelineId, CachedPipelineState, CachedRenderPipelineId,
+        CommandEncoder, ComputePipeline, LoadOp, Operations, PipelineCache, PipelineCacheError,
+        PreparedBindGroup, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
+        ShaderType, SpecializedComputePipeline, SpecializedComputePipelines,
+        SpecializedRenderPipeline, SpecializedRenderPipelines, StoreOp, TextureUsages,
     },
-    renderer::RenderDevice,
-    sync_world::MainEntity,
-    Render, RenderApp, RenderSet,
+    renderer::{RenderAdapterInfo, RenderDevice, RenderQueue},
+    storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
+    sync_world::{MainEntity, RenderEntity},
+    texture::GpuImage,
+    view::{ExtractedView, ViewUniformOffset, ViewUniforms},
+    Extract, ExtractSchedule, Render, RenderSet,
+};
+use wgpu::{Backend, Color, Features, RenderPass};
+
+use disqualified::ShortName;
+
+use crate::{
+    meta::JobMarker,
+    validation::{EntryPointValidation, JobPipelineId},
 };
 
-use super::GraphicsJob;
+use super::{ext::InitGraphicsJobExt, job_sub_app_mut, GraphicsJob, JobExecutionSettings};
 
 /// The status of a job input
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -59,6 +84,11 @@ pub type JobInputItem<'a, J, In> = <In as JobInput<J>>::Item<'a>;
 /// Note: while there is no blanket impl for [`JobInput`] for all
 /// [`ReadOnlyQueryData`] types, it *is* implemented for all single
 /// components, [`Entity`], [`MainEntity`], and [`Option`].
+///
+/// Only the job component itself is extracted to the render world automatically.
+/// To use other components on the job entity as input, call
+/// [`extract_job_component`](crate::ext::InitGraphicsJobExt::extract_job_component)
+/// on [`App`] for each of them.
 pub trait JobInput<J: GraphicsJob> {
     type Data: ReadOnlyQueryData;
     type Item<'a>;
@@ -76,6 +106,16 @@ pub trait JobInput<J: GraphicsJob> {
 
     /// returns the actual job input item.
     fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a>;
+
+    /// encodes any commands needed after the job has successfully run. For
+    /// example, an implementor may copy a buffer written by the job to be
+    /// read back to the CPU.
+    fn post_run(
+        _data: QueryItem<Self::Data>,
+        _world: &World,
+        _command_encoder: &mut CommandEncoder,
+    ) {
+    }
 }
 
 macro_rules! impl_job_input_tuple {
@@ -103,6 +143,16 @@ macro_rules! impl_job_input_tuple {
                 let ($($t,)*) = data;
                 ($(<$T as JobInput<J>>::get($t, world),)*)
             }
+
+            #[allow(unused_variables)]
+            fn post_run(
+                data: QueryItem<Self::Data>,
+                world: &World,
+                command_encoder: &mut CommandEncoder,
+            ) {
+                let ($($t,)*) = data;
+                $(<$T as JobInput<J>>::post_run($t, world, command_encoder);)*
+            }
         }
     }
 }
@@ -137,6 +187,8 @@ impl<J: GraphicsJob> JobInput<J> for MainEntity {
     }
 }
 
+/// Note: the component must be present on the render-world job entity. See
+/// [`extract_job_component`](crate::ext::InitGraphicsJobExt::extract_job_component).
 impl<'t, T: Component, J: GraphicsJob> JobInput<J> for &'t T {
     type Data = &'t T;
 
@@ -165,77 +217,457 @@ impl<T: ReadOnlyQueryData, J: GraphicsJob> JobInput<J> for Option<T> {
     }
 }
 
+/// Yields the [`Backend`] the render device is running on, so a job may adapt its
+/// commands for constrained backends like WebGPU. Jobs will fail if the adapter
+/// info isn't available.
+///
+/// To configure jobs per backend before they're spawned, read the [`RenderAdapterInfo`]
+/// resource, which is also available in the main world once the app has finished
+/// building. For example, a job may be spawned with a different [`JobComputePipeline`]
+/// key on [`Backend::BrowserWebGpu`] to select a pipeline variant without push constants.
+impl<J: GraphicsJob> JobInput<J> for Backend {
+    type Data = ();
+
+    type Item<'a> = Backend;
+
+    fn status(_data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        match world.get_resource::<RenderAdapterInfo>() {
+            Some(_) => JobInputStatus::Ready,
+            None => JobInputStatus::Fail,
+        }
+    }
+
+    fn get<'a>(_data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        world.resource::<RenderAdapterInfo>().backend
+    }
+}
+
+/// A [`JobInput`] type that yields the [`RenderQueue`], for jobs that need to write to
+/// buffers or textures with `write_buffer` or `write_texture` right before encoding.
+///
+/// Note: queue writes are staged, and happen before the next submission, which includes
+/// the commands of *every* job executed in the same frame. So, a write will be visible
+/// to jobs that ran before it that frame, and shouldn't target resources that earlier
+/// jobs expect to read unchanged.
+pub struct JobRenderQueue;
+
+impl<J: GraphicsJob> JobInput<J> for JobRenderQueue {
+    type Data = ();
+
+    type Item<'a> = &'a RenderQueue;
+
+    fn status(_data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        JobInputStatus::Ready
+    }
+
+    fn get<'a>(_data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        world.resource::<RenderQueue>()
+    }
+}
+
+/// A [`JobInput`] type that yields a render-world [`Resource`] `R`, for data shared
+/// between jobs such as a sampler or a uniform buffer. Jobs wait until the resource
+/// is inserted.
+///
+/// For resources that a job may do without, see [`JobResourceOr`].
+pub struct JobResource<R>(PhantomData<R>);
+
+impl<J: GraphicsJob, R: Resource> JobInput<J> for JobResource<R> {
+    type Data = ();
+
+    type Item<'a> = &'a R;
+
+    fn status(_data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        if world.contains_resource::<R>() {
+            JobInputStatus::Ready
+        } else {
+            JobInputStatus::Wait
+        }
+    }
+
+    fn get<'a>(_data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        world.resource::<R>()
+    }
+}
+
+/// A [`JobInput`] type that yields a render-world [`Resource`] `R` if it exists.
+/// Unlike [`JobResource`], jobs don't wait for the resource.
+pub struct JobResourceOr<R>(PhantomData<R>);
+
+impl<J: GraphicsJob, R: Resource> JobInput<J> for JobResourceOr<R> {
+    type Data = ();
+
+    type Item<'a> = Option<&'a R>;
+
+    fn status(_data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        JobInputStatus::Ready
+    }
+
+    fn get<'a>(_data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        world.get_resource::<R>()
+    }
+}
+
+/// A [`JobInput`] type that is ready as soon as either of two alternative inputs is,
+/// for jobs that can run with whichever data source is available first. Yields
+/// [`EitherItem::Left`] if `A` is ready, and [`EitherItem::Right`] otherwise. Either
+/// side's components may be missing from the job entity, which counts as that side
+/// failing; jobs only fail once both sides have.
+///
+/// Note: unlike tuples, only the `post_run` of the side that was yielded is run.
+pub struct Either<A, B>(PhantomData<(A, B)>);
+
+/// The item yielded by [`Either`]
+pub enum EitherItem<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// The status of an input wrapped by [`Either`] or [`Opt`], failing if its data is missing
+fn inner_status<J: GraphicsJob, T: JobInput<J>>(
+    entity: &EntityRef,
+    world: &World,
+) -> JobInputStatus {
+    entity
+        .get_components::<T::Data>()
+        .map_or(JobInputStatus::Fail, |data| T::status(data, world))
+}
+
+impl<J: GraphicsJob, A: JobInput<J>, B: JobInput<J>> JobInput<J> for Either<A, B> {
+    type Data = EntityRef<'static>;
+
+    type Item<'a> = EitherItem<A::Item<'a>, B::Item<'a>>;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            app.add_plugins((A::plugin(), B::plugin()));
+        }
+    }
+
+    fn status(entity: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        match (
+            inner_status::<J, A>(&entity, world),
+            inner_status::<J, B>(&entity, world),
+        ) {
+            (JobInputStatus::Ready, _) | (_, JobInputStatus::Ready) => JobInputStatus::Ready,
+            (JobInputStatus::Fail, JobInputStatus::Fail) => JobInputStatus::Fail,
+            _ => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(entity: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        if inner_status::<J, A>(&entity, world) == JobInputStatus::Ready {
+            let data = entity
+                .get_components::<A::Data>()
+                .expect("the ready side should have its data");
+            EitherItem::Left(A::get(data, world))
+        } else {
+            let data = entity
+                .get_components::<B::Data>()
+                .expect("the ready side should have its data");
+            EitherItem::Right(B::get(data, world))
+        }
+    }
+
+    fn post_run(
+        entity: QueryItem<Self::Data>,
+        world: &World,
+        command_encoder: &mut CommandEncoder,
+    ) {
+        if inner_status::<J, A>(&entity, world) == JobInputStatus::Ready {
+            if let Some(data) = entity.get_components::<A::Data>() {
+                A::post_run(data, world, command_encoder);
+            }
+        } else if let Some(data) = entity.get_components::<B::Data>() {
+            B::post_run(data, world, command_encoder);
+        }
+    }
+}
+
+/// A [`JobInput`] type wrapping another input that the job may do without. It's
+/// always ready, and yields `Some` with the inner item only if the inner input's
+/// components are present on the job entity and it's ready, and `None` otherwise.
+///
+/// Unlike `Option<T>`, which only wraps query data, this wraps any [`JobInput`],
+/// including its `plugin` and `post_run`. The inner input is never waited on.
+pub struct Opt<T>(PhantomData<T>);
+
+impl<J: GraphicsJob, T: JobInput<J>> JobInput<J> for Opt<T> {
+    type Data = EntityRef<'static>;
+
+    type Item<'a> = Option<T::Item<'a>>;
+
+    fn plugin() -> impl Plugin {
+        T::plugin()
+    }
+
+    fn status(_entity: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        JobInputStatus::Ready
+    }
+
+    fn get<'a>(entity: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        if inner_status::<J, T>(&entity, world) != JobInputStatus::Ready {
+            return None;
+        }
+        let data = entity.get_components::<T::Data>()?;
+        Some(T::get(data, world))
+    }
+
+    fn post_run(
+        entity: QueryItem<Self::Data>,
+        world: &World,
+        command_encoder: &mut CommandEncoder,
+    ) {
+        if inner_status::<J, T>(&entity, world) != JobInputStatus::Ready {
+            return;
+        }
+        if let Some(data) = entity.get_components::<T::Data>() {
+            T::post_run(data, world, command_encoder);
+        }
+    }
+}
+
+/// A [`JobInput`] type that prepares the job itself as a bind group, using its
+/// [`AsBindGroup`] implementation.
+///
+/// Layouts derived with `AsBindGroup` never use dynamic offsets, so the bind group
+/// is set with empty offsets: `set_bind_group(0, &bind_group.bind_group, &[])`. For
+/// manual implementations whose layout does, see [`JobDynamicOffsets`].
 pub struct JobAsBindGroup;
 
 impl<J: GraphicsJob + AsBindGroup> JobInput<J> for JobAsBindGroup {
-    type Data = Option<Read<PreparedJobBindGroup<J>>>;
+    type Data = (
+        Option<Read<PreparedJobBindGroup<J>>>,
+        Has<JobBindGroupFailed<J>>,
+    );
 
     type Item<'a> = &'a PreparedBindGroup<<J as AsBindGroup>::Data>;
 
     fn plugin() -> impl Plugin {
-        JobAsBindGroupPlugin::<J>(PhantomData)
+        |app: &mut App| {
+            if !app.is_plugin_added::<JobBindGroupPlugin<J>>() {
+                app.add_plugins(JobBindGroupPlugin::<J>(PhantomData));
+            }
+        }
     }
 
-    fn status(data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
-        match data {
-            Some(_) => JobInputStatus::Ready,
-            None => JobInputStatus::Wait,
+    fn status((prepared, failed): QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        bind_group_status(prepared.is_some(), failed)
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.0.unwrap().0
+    }
+}
+
+/// A [`JobInput`] type that prepares a component `B` on the job entity as a bind group,
+/// using its [`AsBindGroup`] implementation. The bind group should be set at `INDEX`,
+/// which is also available as [`JobBindGroup::INDEX`]. The component is extracted
+/// automatically, and re-extracted whenever it changes.
+///
+/// This allows jobs to use several bind groups, for example a small group of parameters
+/// and a large group of data: `type In = (JobBindGroup<0, Params>, JobBindGroup<1, Data>)`.
+/// Each bind group type is prepared separately, with its own layout.
+/// Layouts with dynamic offsets are set with the offsets in [`JobDynamicOffsets`].
+pub struct JobBindGroup<const INDEX: u32, B>(PhantomData<B>);
+
+impl<const INDEX: u32, B> JobBindGroup<INDEX, B> {
+    /// The index the bind group should be set at
+    pub const INDEX: u32 = INDEX;
+}
+
+impl<const INDEX: u32, J: GraphicsJob, B: Component + Clone + AsBindGroup> JobInput<J>
+    for JobBindGroup<INDEX, B>
+{
+    type Data = (
+        Option<Read<PreparedJobBindGroup<B>>>,
+        Has<JobBindGroupFailed<B>>,
+    );
+
+    type Item<'a> = &'a PreparedBindGroup<<B as AsBindGroup>::Data>;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            app.extract_job_component::<B>();
+            if !app.is_plugin_added::<JobBindGroupPlugin<B>>() {
+                app.add_plugins(JobBindGroupPlugin::<B>(PhantomData));
+            }
+            #[cfg(feature = "validate-layouts")]
+            crate::validation::layouts::register_bind_group_index::<B>(app, INDEX);
+        }
+    }
+
+    fn status((prepared, failed): QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        bind_group_status(prepared.is_some(), failed)
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.0.unwrap().0
+    }
+}
+
+/// The dynamic offsets to set the bind group `B` with, for bind groups prepared by
+/// [`JobAsBindGroup`] or [`JobBindGroup`] whose layout has dynamic offsets. These
+/// aren't part of [`PreparedBindGroup`], so they're given separately, and may be
+/// used as a [`JobInput`] yielding the offsets, or an empty slice if the job has none.
+/// The component is extracted automatically, and re-extracted whenever it changes.
+///
+/// For example, a job binding one element of a dynamic uniform buffer:
+///
+/// ```ignore
+/// commands.spawn((
+///     MyJob { .. },
+///     JobDynamicOffsets::<MyJob>::new([element_index * element_stride]),
+/// ));
+///
+/// impl GraphicsJob for MyJob {
+///     type In = (JobAsBindGroup, JobDynamicOffsets<MyJob>, JobComputePipeline<MyPipeline>);
+///
+///     fn run(&self, .., (bind_group, offsets, pipeline): JobInputItem<Self, Self::In>) -> .. {
+///         let mut pass = command_encoder.begin_compute_pass(&Default::default());
+///         pass.set_bind_group(0, &bind_group.bind_group, offsets);
+///         ..
+///     }
+/// }
+/// ```
+#[derive(Component)]
+pub struct JobDynamicOffsets<B>(pub Vec<u32>, PhantomData<fn() -> B>);
+
+impl<B> JobDynamicOffsets<B> {
+    pub fn new(offsets: impl IntoIterator<Item = u32>) -> Self {
+        Self(offsets.into_iter().collect(), PhantomData)
+    }
+}
+
+impl<B> Clone for JobDynamicOffsets<B> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<B: 'static> ExtractComponent for JobDynamicOffsets<B> {
+    type QueryData = Read<JobDynamicOffsets<B>>;
+
+    type QueryFilter = ();
+
+    type Out = JobDynamicOffsets<B>;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+impl<J: GraphicsJob, B: 'static> JobInput<J> for JobDynamicOffsets<B> {
+    type Data = Option<Read<JobDynamicOffsets<B>>>;
+
+    type Item<'a> = &'a [u32];
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<ExtractComponentPlugin<JobDynamicOffsets<B>>>() {
+                app.add_plugins(ExtractComponentPlugin::<JobDynamicOffsets<B>>::default());
+            }
         }
     }
 
+    fn status(_data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        JobInputStatus::Ready
+    }
+
     fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
-        &data.unwrap().0
+        data.map_or(&[][..], |offsets| &offsets.0[..])
     }
 }
 
-struct JobAsBindGroupPlugin<J>(PhantomData<J>);
+/// Prepares the component `B` as a bind group on every job entity it's found on.
+struct JobBindGroupPlugin<B>(PhantomData<B>);
 
-impl<J: GraphicsJob + AsBindGroup> Plugin for JobAsBindGroupPlugin<J> {
+impl<B: Component + AsBindGroup> Plugin for JobBindGroupPlugin<B> {
     fn build(&self, app: &mut App) {
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
+            // input statuses are read with `get_components`, which fails for components
+            // that aren't registered, so jobs would fail before any bind group is prepared
+            let world = render_app.world_mut();
+            world.register_component::<PreparedJobBindGroup<B>>();
+            world.register_component::<JobBindGroupFailed<B>>();
+
+            render_app.add_systems(
+                Render,
+                prepare_job_bind_group::<B>.in_set(RenderSet::PrepareBindGroups),
+            );
+
+            #[cfg(feature = "validate-layouts")]
             render_app.add_systems(
                 Render,
-                prepare_job_bind_group::<J>.in_set(RenderSet::PrepareBindGroups),
+                crate::validation::layouts::queue_bind_group_layout_validation::<B>
+                    .in_set(RenderSet::PrepareBindGroups),
             );
         }
     }
 
     fn finish(&self, app: &mut App) {
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
-            render_app.init_resource::<JobBindGroupLayout<J>>();
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.init_resource::<JobBindGroupLayout<B>>();
         }
     }
 }
 
-/// A [`JobInput`] type that prepares the graphics job type *itself* as a bind group,
-/// using its [`AsBindGroup`] implementation.
+/// Bind groups are prepared once their resources are ready, so jobs wait until then.
+/// Bind groups that fail to be prepared for any other reason fail the job.
+fn bind_group_status(prepared: bool, failed: bool) -> JobInputStatus {
+    match (prepared, failed) {
+        (_, true) => JobInputStatus::Fail,
+        (true, false) => JobInputStatus::Ready,
+        (false, false) => JobInputStatus::Wait,
+    }
+}
+
+/// Marks a job whose bind group `B` couldn't be prepared, for a reason other
+/// than its resources not being ready yet.
 #[derive(Component)]
-pub struct PreparedJobBindGroup<J: GraphicsJob + AsBindGroup>(
-    PreparedBindGroup<<J as AsBindGroup>::Data>,
-);
+#[doc(hidden)]
+pub struct JobBindGroupFailed<B>(PhantomData<B>);
+
+/// A component `B` on a job entity, prepared as a bind group by [`JobAsBindGroup`]
+/// or [`JobBindGroup`].
+#[derive(Component)]
+pub struct PreparedJobBindGroup<B: AsBindGroup>(PreparedBindGroup<<B as AsBindGroup>::Data>);
 
 #[derive(Resource)]
-struct JobBindGroupLayout<J: GraphicsJob + AsBindGroup>(BindGroupLayout, PhantomData<J>);
+struct JobBindGroupLayout<B: AsBindGroup>(BindGroupLayout, PhantomData<B>);
 
-impl<J: GraphicsJob + AsBindGroup> FromWorld for JobBindGroupLayout<J> {
+impl<B: AsBindGroup + Send + Sync + 'static> FromWorld for JobBindGroupLayout<B> {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
-        Self(J::bind_group_layout(render_device), PhantomData)
+        Self(B::bind_group_layout(render_device), PhantomData)
     }
 }
 
-fn prepare_job_bind_group<J: GraphicsJob + AsBindGroup>(
-    jobs: Query<(Entity, &J)>,
-    layout: Res<JobBindGroupLayout<J>>,
+fn prepare_job_bind_group<B: Component + AsBindGroup>(
+    jobs: Query<(Entity, &B), Without<JobBindGroupFailed<B>>>,
+    layout: Res<JobBindGroupLayout<B>>,
     render_device: Res<RenderDevice>,
-    mut param: StaticSystemParam<<J as AsBindGroup>::Param>,
+    mut param: StaticSystemParam<<B as AsBindGroup>::Param>,
     mut commands: Commands,
 ) {
-    for (entity, job) in &jobs {
-        if let Ok(bind_group) = job.as_bind_group(&layout.0, &render_device, &mut param) {
-            commands
-                .entity(entity)
-                .insert(PreparedJobBindGroup::<J>(bind_group));
+    for (entity, bind_group_source) in &jobs {
+        match bind_group_source.as_bind_group(&layout.0, &render_device, &mut param) {
+            Ok(bind_group) => {
+                commands
+                    .entity(entity)
+                    .insert(PreparedJobBindGroup::<B>(bind_group));
+            }
+            Err(AsBindGroupError::RetryNextUpdate) => {}
+            Err(err) => {
+                error!(
+                    "failed to prepare bind group `{}` for a job: {}",
+                    ShortName::of::<B>(),
+                    err
+                );
+                commands
+                    .entity(entity)
+                    .insert(JobBindGroupFailed::<B>(PhantomData));
+            }
         }
     }
 }
@@ -252,6 +684,8 @@ impl<P: SpecializedRenderPipeline<Key: Send + Sync> + Resource + FromWorld>
 
 /// A [`JobInput`] type that sets up a [`RenderPipeline`] for a job. This component must be
 /// added to a job as it is spawned in order to setup the pipeline.
+///
+/// Fallback keys may be given with [`JobRenderPipelineFallbacks`].
 #[derive(Component)]
 pub struct JobRenderPipeline<P: SpecializedJobRenderPipeline>(pub P::Key);
 
@@ -262,7 +696,10 @@ impl<P: SpecializedJobRenderPipeline<Key: Default>> Default for JobRenderPipelin
 }
 
 impl<J: GraphicsJob, P: SpecializedJobRenderPipeline> JobInput<J> for JobRenderPipeline<P> {
-    type Data = Option<Read<JobRenderPipelineId<P>>>;
+    type Data = (
+        Option<Read<JobRenderPipelineId<P>>>,
+        Option<Read<JobRenderPipelineFallbacks<P>>>,
+    );
 
     type Item<'a> = &'a RenderPipeline;
 
@@ -270,24 +707,27 @@ impl<J: GraphicsJob, P: SpecializedJobRenderPipeline> JobInput<J> for JobRenderP
         JobRenderPipelinePlugin::<P>(PhantomData)
     }
 
-    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
-        let Some(JobRenderPipelineId(id, _)) = data else {
+    fn status((id, fallbacks): QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let Some(JobRenderPipelineId(id, variant, _)) = id else {
             return JobInputStatus::Wait;
         };
-        if matches!(
+        if world
+            .resource::<EntryPointValidation>()
+            .is_invalid(JobPipelineId::Render(*id))
+        {
+            return JobInputStatus::Fail;
+        }
+        let fallbacks_left = fallbacks.is_some_and(|fallbacks| *variant < fallbacks.0.len());
+        pipeline_status(
             world
                 .resource::<PipelineCache>()
                 .get_render_pipeline_state(*id),
-            CachedPipelineState::Ok(_)
-        ) {
-            JobInputStatus::Ready
-        } else {
-            JobInputStatus::Wait
-        }
+            fallbacks_left,
+        )
     }
 
     fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
-        let id = data.unwrap().0;
+        let id = data.0.unwrap().0;
         world
             .resource::<PipelineCache>()
             .get_render_pipeline(id)
@@ -313,10 +753,35 @@ impl<P: SpecializedJobRenderPipeline> ExtractComponent for JobRenderPipeline<P>
     }
 }
 
+/// A list of fallback keys for a [`JobRenderPipeline`], tried in order if specializing
+/// the pipeline with the previous key fails, for example because a shader uses a feature
+/// the device doesn't support. This component must be added to a job as it is spawned.
+#[derive(Component)]
+pub struct JobRenderPipelineFallbacks<P: SpecializedJobRenderPipeline>(pub Vec<P::Key>);
+
+impl<P: SpecializedJobRenderPipeline> Clone for JobRenderPipelineFallbacks<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<P: SpecializedJobRenderPipeline> ExtractComponent for JobRenderPipelineFallbacks<P> {
+    type QueryData = Read<JobRenderPipelineFallbacks<P>>;
+
+    type QueryFilter = ();
+
+    type Out = JobRenderPipelineFallbacks<P>;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
 #[derive(Component)]
 #[doc(hidden)]
 pub struct JobRenderPipelineId<P: SpecializedJobRenderPipeline>(
     CachedRenderPipelineId,
+    usize,
     PhantomData<P>,
 );
 
@@ -324,9 +789,12 @@ struct JobRenderPipelinePlugin<P: SpecializedJobRenderPipeline>(PhantomData<P>);
 
 impl<P: SpecializedJobRenderPipeline> Plugin for JobRenderPipelinePlugin<P> {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ExtractComponentPlugin::<JobRenderPipeline<P>>::default());
+        app.add_plugins((
+            ExtractComponentPlugin::<JobRenderPipeline<P>>::default(),
+            ExtractComponentPlugin::<JobRenderPipelineFallbacks<P>>::default(),
+        ));
 
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
             render_app
                 .init_resource::<SpecializedRenderPipelines<P>>()
                 .add_systems(
@@ -337,24 +805,64 @@ impl<P: SpecializedJobRenderPipeline> Plugin for JobRenderPipelinePlugin<P> {
     }
 
     fn finish(&self, app: &mut App) {
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
             render_app.init_resource::<P>();
         }
     }
 }
 
 fn queue_job_render_pipelines<P: SpecializedJobRenderPipeline>(
-    job_pipelines: Query<(Entity, &JobRenderPipeline<P>), Changed<JobRenderPipeline<P>>>,
+    job_pipelines: Query<(
+        Entity,
+        Ref<JobRenderPipeline<P>>,
+        Option<&JobRenderPipelineFallbacks<P>>,
+        Option<&JobRenderPipelineId<P>>,
+    )>,
     pipeline_cache: Res<PipelineCache>,
     base_pipeline: Res<P>,
+    exec_settings: Res<JobExecutionSettings>,
     mut specializer: ResMut<SpecializedRenderPipelines<P>>,
+    mut validation: ResMut<EntryPointValidation>,
     mut commands: Commands,
 ) {
-    for (entity, job_pipeline) in &job_pipelines {
-        let id = specializer.specialize(&pipeline_cache, &base_pipeline, job_pipeline.0.clone());
+    for (entity, job_pipeline, fallbacks, pipeline_id) in &job_pipelines {
+        let fallbacks = fallbacks.map_or(&[][..], |fallbacks| &fallbacks.0[..]);
+        let variant = match pipeline_id {
+            Some(JobRenderPipelineId(id, variant, _)) => {
+                let errored = matches!(
+                    pipeline_cache.get_render_pipeline_state(*id),
+                    CachedPipelineState::Err(_)
+                );
+                if errored && *variant < fallbacks.len() {
+                    variant + 1
+                } else if job_pipeline.is_changed() {
+                    *variant
+                } else {
+                    continue;
+                }
+            }
+            None => 0,
+        };
+
+        let key = pipeline_variant_key(&job_pipeline.0, fallbacks, variant);
+        let id = specializer.specialize(&pipeline_cache, &base_pipeline, key);
+        if exec_settings.validate_entry_points {
+            validation.queue(
+                JobPipelineId::Render(id),
+                ShortName::of::<JobRenderPipeline<P>>(),
+            );
+        }
         commands
             .entity(entity)
-            .insert(JobRenderPipelineId::<P>(id, PhantomData));
+            .insert(JobRenderPipelineId::<P>(id, variant, PhantomData));
+
+        #[cfg(feature = "validate-layouts")]
+        commands
+            .entity(entity)
+            .insert(crate::validation::layouts::JobLayoutPipeline(
+                JobPipelineId::Render(id),
+                ShortName::of::<JobRenderPipeline<P>>(),
+            ));
     }
 }
 
@@ -370,8 +878,15 @@ impl<P: SpecializedComputePipeline<Key: Send + Sync> + Resource + FromWorld>
 
 /// A [`JobInput`] type that sets up a [`ComputePipeline`] for a job. This component must be
 /// added to a job as it is spawned in order to setup the pipeline.
+///
+/// The pipeline is specialized with the key given here, so jobs of the same type may use
+/// different pipeline variants. For example, a key may select between several entry points
+/// in the same shader by setting `entry_point` in the specialized descriptor. See the
+/// `entry_points` example in the repo.
+///
+/// Fallback keys may be given with [`JobComputePipelineFallbacks`].
 #[derive(Component)]
-pub struct JobComputePipeline<P: SpecializedJobComputePipeline>(P::Key);
+pub struct JobComputePipeline<P: SpecializedJobComputePipeline>(pub P::Key);
 
 impl<P: SpecializedJobComputePipeline<Key: Default>> Default for JobComputePipeline<P> {
     fn default() -> Self {
@@ -380,7 +895,10 @@ impl<P: SpecializedJobComputePipeline<Key: Default>> Default for JobComputePipel
 }
 
 impl<J: GraphicsJob, P: SpecializedJobComputePipeline> JobInput<J> for JobComputePipeline<P> {
-    type Data = Option<Read<JobComputePipelineId<P>>>;
+    type Data = (
+        Option<Read<JobComputePipelineId<P>>>,
+        Option<Read<JobComputePipelineFallbacks<P>>>,
+    );
 
     type Item<'a> = &'a ComputePipeline;
 
@@ -388,24 +906,27 @@ impl<J: GraphicsJob, P: SpecializedJobComputePipeline> JobInput<J> for JobComput
         JobComputePipelinePlugin::<P>(PhantomData)
     }
 
-    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
-        let Some(JobComputePipelineId(id, _)) = data else {
+    fn status((id, fallbacks): QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let Some(JobComputePipelineId(id, variant, _)) = id else {
             return JobInputStatus::Wait;
         };
-        if matches!(
+        if world
+            .resource::<EntryPointValidation>()
+            .is_invalid(JobPipelineId::Compute(*id))
+        {
+            return JobInputStatus::Fail;
+        }
+        let fallbacks_left = fallbacks.is_some_and(|fallbacks| *variant < fallbacks.0.len());
+        pipeline_status(
             world
                 .resource::<PipelineCache>()
                 .get_compute_pipeline_state(*id),
-            CachedPipelineState::Ok(_)
-        ) {
-            JobInputStatus::Ready
-        } else {
-            JobInputStatus::Wait
-        }
+            fallbacks_left,
+        )
     }
 
     fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
-        let id = data.unwrap().0;
+        let id = data.0.unwrap().0;
         world
             .resource::<PipelineCache>()
             .get_compute_pipeline(id)
@@ -431,10 +952,35 @@ impl<P: SpecializedJobComputePipeline> ExtractComponent for JobComputePipeline<P
     }
 }
 
+/// A list of fallback keys for a [`JobComputePipeline`], tried in order if specializing
+/// the pipeline with the previous key fails, for example because a shader uses a feature
+/// the device doesn't support. This component must be added to a job as it is spawned.
+#[derive(Component)]
+pub struct JobComputePipelineFallbacks<P: SpecializedJobComputePipeline>(pub Vec<P::Key>);
+
+impl<P: SpecializedJobComputePipeline> Clone for JobComputePipelineFallbacks<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<P: SpecializedJobComputePipeline> ExtractComponent for JobComputePipelineFallbacks<P> {
+    type QueryData = Read<JobComputePipelineFallbacks<P>>;
+
+    type QueryFilter = ();
+
+    type Out = JobComputePipelineFallbacks<P>;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
 #[derive(Component)]
 #[doc(hidden)]
 pub struct JobComputePipelineId<P: SpecializedJobComputePipeline>(
     CachedComputePipelineId,
+    usize,
     PhantomData<P>,
 );
 
@@ -442,9 +988,12 @@ struct JobComputePipelinePlugin<P: SpecializedJobComputePipeline>(PhantomData<P>
 
 impl<P: SpecializedJobComputePipeline> Plugin for JobComputePipelinePlugin<P> {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ExtractComponentPlugin::<JobComputePipeline<P>>::default());
+        app.add_plugins((
+            ExtractComponentPlugin::<JobComputePipeline<P>>::default(),
+            ExtractComponentPlugin::<JobComputePipelineFallbacks<P>>::default(),
+        ));
 
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
             render_app
                 .init_resource::<SpecializedComputePipelines<P>>()
                 .add_systems(
@@ -455,23 +1004,1608 @@ impl<P: SpecializedJobComputePipeline> Plugin for JobComputePipelinePlugin<P> {
     }
 
     fn finish(&self, app: &mut App) {
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
             render_app.init_resource::<P>();
         }
     }
 }
 
 fn queue_job_compute_pipelines<P: SpecializedJobComputePipeline>(
-    job_pipelines: Query<(Entity, &JobComputePipeline<P>), Changed<JobComputePipeline<P>>>,
+    job_pipelines: Query<(
+        Entity,
+        Ref<JobComputePipeline<P>>,
+        Option<&JobComputePipelineFallbacks<P>>,
+        Option<&JobComputePipelineId<P>>,
+    )>,
     pipeline_cache: Res<PipelineCache>,
     base_pipeline: Res<P>,
+    exec_settings: Res<JobExecutionSettings>,
     mut specializer: ResMut<SpecializedComputePipelines<P>>,
+    mut validation: ResMut<EntryPointValidation>,
     mut commands: Commands,
 ) {
-    for (entity, job_pipeline) in &job_pipelines {
-        let id = specializer.specialize(&pipeline_cache, &base_pipeline, job_pipeline.0.clone());
+    for (entity, job_pipeline, fallbacks, pipeline_id) in &job_pipelines {
+        let fallbacks = fallbacks.map_or(&[][..], |fallbacks| &fallbacks.0[..]);
+        let variant = match pipeline_id {
+            Some(JobComputePipelineId(id, variant, _)) => {
+                let errored = matches!(
+                    pipeline_cache.get_compute_pipeline_state(*id),
+                    CachedPipelineState::Err(_)
+                );
+                if errored && *variant < fallbacks.len() {
+                    variant + 1
+                } else if job_pipeline.is_changed() {
+                    *variant
+                } else {
+                    continue;
+                }
+            }
+            None => 0,
+        };
+
+        let key = pipeline_variant_key(&job_pipeline.0, fallbacks, variant);
+        let id = specializer.specialize(&pipeline_cache, &base_pipeline, key);
+        if exec_settings.validate_entry_points {
+            validation.queue(
+                JobPipelineId::Compute(id),
+                ShortName::of::<JobComputePipeline<P>>(),
+            );
+        }
+        commands
+            .entity(entity)
+            .insert(JobComputePipelineId::<P>(id, variant, PhantomData));
+
+        #[cfg(feature = "validate-layouts")]
         commands
             .entity(entity)
-            .insert(JobComputePipelineId::<P>(id, PhantomData));
+            .insert(crate::validation::layouts::JobLayoutPipeline(
+                JobPipelineId::Compute(id),
+                ShortName::of::<JobComputePipeline<P>>(),
+            ));
+    }
+}
+
+/// Pipelines are ready once compiled. Pipelines that failed to compile fail the job,
+/// unless there's a fallback variant left to try, rather than waiting until the job
+/// times out. Shaders that haven't loaded yet are retried by the [`PipelineCache`],
+/// so they're waited on.
+fn pipeline_status(state: &CachedPipelineState, fallbacks_left: bool) -> JobInputStatus {
+    match state {
+        CachedPipelineState::Ok(_) => JobInputStatus::Ready,
+        CachedPipelineState::Err(
+            PipelineCacheError::ShaderNotLoaded(_)
+            | PipelineCacheError::ShaderImportNotYetAvailable,
+        ) => JobInputStatus::Wait,
+        CachedPipelineState::Err(_) if !fallbacks_left => JobInputStatus::Fail,
+        _ => JobInputStatus::Wait,
+    }
+}
+
+/// Returns the key for a pipeline variant, where variant `0` is the primary key,
+/// and each following variant is the next fallback key.
+fn pipeline_variant_key<K: Clone>(primary: &K, fallbacks: &[K], variant: usize) -> K {
+    match variant {
+        0 => primary.clone(),
+        i => fallbacks[i - 1].clone(),
+    }
+}
+
+/// A [`JobInput`] type that waits for a [`Mesh`] to be uploaded to the GPU, and yields
+/// its vertex and index buffers. This component must be added to a job as it is spawned.
+#[derive(Component, Clone)]
+pub struct JobMesh(pub Handle<Mesh>);
+
+/// The GPU buffers of a mesh, as provided by [`JobMesh`].
+///
+/// Note: meshes are packed into shared buffers by the [`MeshAllocator`], so jobs
+/// should only read and write the ranges given here rather than the whole buffer.
+pub struct JobMeshBuffers<'a> {
+    pub mesh: &'a RenderMesh,
+    /// The buffer containing the mesh's vertex data
+    pub vertex_buffer: &'a Buffer,
+    /// The range of vertices in `vertex_buffer` belonging to this mesh
+    pub vertex_range: Range<u32>,
+    /// The buffer containing the mesh's index data, and the range of indices
+    /// belonging to this mesh, if the mesh is indexed.
+    pub index_buffer: Option<(&'a Buffer, Range<u32>)>,
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobMesh {
+    type Data = Read<JobMesh>;
+
+    type Item<'a> = JobMeshBuffers<'a>;
+
+    fn plugin() -> impl Plugin {
+        JobMeshPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let (Some(meshes), Some(mesh_allocator)) = (
+            world.get_resource::<RenderAssets<RenderMesh>>(),
+            world.get_resource::<MeshAllocator>(),
+        ) else {
+            return JobInputStatus::Fail;
+        };
+
+        let mesh_id = data.0.id();
+        let Some(mesh) = meshes.get(mesh_id) else {
+            return JobInputStatus::Wait;
+        };
+
+        let vertices_ready = mesh_allocator.mesh_vertex_slice(&mesh_id).is_some();
+        let indices_ready = match mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed { .. } => {
+                mesh_allocator.mesh_index_slice(&mesh_id).is_some()
+            }
+            RenderMeshBufferInfo::NonIndexed => true,
+        };
+
+        if vertices_ready && indices_ready {
+            JobInputStatus::Ready
+        } else {
+            JobInputStatus::Wait
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        let mesh_id = data.0.id();
+        let mesh = world
+            .resource::<RenderAssets<RenderMesh>>()
+            .get(mesh_id)
+            .expect("mesh should be ready by this point");
+        let mesh_allocator = world.resource::<MeshAllocator>();
+        let vertex_slice = mesh_allocator
+            .mesh_vertex_slice(&mesh_id)
+            .expect("mesh vertex buffer should be ready by this point");
+        let index_buffer = mesh_allocator
+            .mesh_index_slice(&mesh_id)
+            .map(|slice| (slice.buffer, slice.range));
+
+        JobMeshBuffers {
+            mesh,
+            vertex_buffer: vertex_slice.buffer,
+            vertex_range: vertex_slice.range,
+            index_buffer,
+        }
+    }
+}
+
+impl ExtractComponent for JobMesh {
+    type QueryData = Read<JobMesh>;
+
+    type QueryFilter = ();
+
+    type Out = JobMesh;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+struct JobMeshPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobMeshPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ExtractComponentPlugin<JobMesh>>() {
+            app.add_plugins(ExtractComponentPlugin::<JobMesh>::default());
+        }
+    }
+}
+
+/// A [`JobInput`] type that waits for an [`Image`] to be uploaded to the GPU, and
+/// yields a [`RenderTarget`] for rendering into it. The image must have
+/// `RENDER_ATTACHMENT` usage. This component must be added to a job as it is spawned.
+///
+/// To be notified once the job has written to the image, see
+/// [`JobOutputImage`](crate::meta::JobOutputImage).
+#[derive(Component, Clone, Debug)]
+pub struct JobRenderTarget {
+    pub image: Handle<Image>,
+    /// The color to clear the image to when a render pass is begun, or `None`
+    /// to load its existing contents. Defaults to transparent black.
+    pub clear_color: Option<Color>,
+}
+
+impl JobRenderTarget {
+    pub fn new(image: Handle<Image>) -> Self {
+        Self {
+            image,
+            clear_color: Some(Color::TRANSPARENT),
+        }
+    }
+}
+
+/// A texture to render into, as provided by [`JobRenderTarget`].
+pub struct RenderTarget<'a> {
+    pub image: &'a GpuImage,
+    clear_color: Option<Color>,
+}
+
+impl RenderTarget<'_> {
+    /// Begins a render pass with the image as its only color attachment, cleared or
+    /// loaded as configured by [`JobRenderTarget::clear_color`].
+    pub fn begin_render_pass<'e>(
+        &self,
+        command_encoder: &'e mut CommandEncoder,
+        label: Option<&str>,
+    ) -> RenderPass<'e> {
+        command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label,
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &self.image.texture_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: self.clear_color.map_or(LoadOp::Load, LoadOp::Clear),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobRenderTarget {
+    type Data = Read<JobRenderTarget>;
+
+    type Item<'a> = RenderTarget<'a>;
+
+    fn plugin() -> impl Plugin {
+        JobRenderTargetPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let Some(images) = world.get_resource::<RenderAssets<GpuImage>>() else {
+            return JobInputStatus::Fail;
+        };
+
+        match images.get(data.image.id()) {
+            Some(_) => JobInputStatus::Ready,
+            None => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        let image = world
+            .resource::<RenderAssets<GpuImage>>()
+            .get(data.image.id())
+            .expect("image should be ready by this point");
+
+        RenderTarget {
+            image,
+            clear_color: data.clear_color,
+        }
+    }
+}
+
+impl ExtractComponent for JobRenderTarget {
+    type QueryData = Read<JobRenderTarget>;
+
+    type QueryFilter = ();
+
+    type Out = JobRenderTarget;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+struct JobRenderTargetPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobRenderTargetPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ExtractComponentPlugin<JobRenderTarget>>() {
+            app.add_plugins(ExtractComponentPlugin::<JobRenderTarget>::default());
+        }
+    }
+}
+
+/// A [`JobInput`] type that waits for an [`Image`] to be uploaded to the GPU, and
+/// yields it for binding as a storage texture, for jobs that bake a texture used
+/// elsewhere, for example by a `StandardMaterial`. The image must have
+/// `STORAGE_BINDING` usage, or the job fails. This component must be added to a job
+/// as it is spawned.
+///
+/// Once the job completes successfully, [`JobImageReady`](crate::JobImageReady) is
+/// triggered on the job entity in the main world, as with
+/// [`JobOutputImage`](crate::meta::JobOutputImage).
+///
+/// # Ordering
+///
+/// With the default [`JobExecutePlacement::BeforeRender`](crate::JobExecutePlacement::BeforeRender),
+/// jobs are submitted in [`JobSet::Execute`](crate::JobSet::Execute), before the
+/// render graph is submitted on the same queue, so a material sampling the image sees
+/// the job's writes in the same frame the job executes. `wgpu` inserts the barriers
+/// between the storage write and the sample itself. This doesn't hold for jobs with
+/// [`RunInGraph`](crate::meta::RunInGraph), which are ordered by their node instead,
+/// or with [`JobExecutePlacement::InCleanup`](crate::JobExecutePlacement::InCleanup),
+/// where the image is first sampled with the job's writes the following frame.
+///
+/// Materials that sample the image before the job executes see its previous contents,
+/// so to avoid showing an unbaked texture, swap the image into the material once
+/// [`JobImageReady`](crate::JobImageReady) is triggered.
+#[derive(Component, Clone, Debug)]
+pub struct JobImageTarget {
+    pub image: Handle<Image>,
+}
+
+impl JobImageTarget {
+    pub fn new(image: Handle<Image>) -> Self {
+        Self { image }
+    }
+}
+
+/// A texture to write to, as provided by [`JobImageTarget`].
+pub struct ImageTarget<'a> {
+    pub image: &'a GpuImage,
+}
+
+impl<'a> ImageTarget<'a> {
+    /// Returns the image's texture view as a binding, for a bind group entry with a
+    /// storage texture layout.
+    pub fn binding(&self) -> BindingResource<'a> {
+        BindingResource::TextureView(&self.image.texture_view)
+    }
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobImageTarget {
+    type Data = Read<JobImageTarget>;
+
+    type Item<'a> = ImageTarget<'a>;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<ExtractComponentPlugin<JobImageTarget>>() {
+                app.add_plugins(ExtractComponentPlugin::<JobImageTarget>::default());
+            }
+        }
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let Some(images) = world.get_resource::<RenderAssets<GpuImage>>() else {
+            return JobInputStatus::Fail;
+        };
+
+        let Some(image) = images.get(data.image.id()) else {
+            return JobInputStatus::Wait;
+        };
+
+        if !image
+            .texture
+            .usage()
+            .contains(TextureUsages::STORAGE_BINDING)
+        {
+            error!(
+                "`{}` can't write to image {:?}, as it doesn't have `STORAGE_BINDING` usage",
+                J::label(),
+                data.image.id()
+            );
+            return JobInputStatus::Fail;
+        }
+
+        JobInputStatus::Ready
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        let image = world
+            .resource::<RenderAssets<GpuImage>>()
+            .get(data.image.id())
+            .expect("image should be ready by this point");
+
+        ImageTarget { image }
+    }
+}
+
+impl ExtractComponent for JobImageTarget {
+    type QueryData = Read<JobImageTarget>;
+
+    type QueryFilter = ();
+
+    type Out = JobImageTarget;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// A [`JobInput`] type that yields a buffer of indirect arguments, for example to
+/// dispatch a compute job with workgroup counts written by an earlier job. The buffer
+/// must have `INDIRECT` usage, or the job fails. This component must be added to a
+/// job as it is spawned.
+///
+/// ```ignore
+/// let mut compute_pass = command_encoder.begin_compute_pass(&Default::default());
+/// compute_pass.set_pipeline(pipeline);
+/// compute_pass.dispatch_workgroups_indirect(indirect_buffer, 0);
+/// ```
+///
+/// To make sure the arguments are written first, the job writing them may be added
+/// to this job's [`JobDependencies`](crate::meta::JobDependencies).
+#[derive(Component, Clone, Debug)]
+pub struct JobIndirectBuffer(pub Buffer);
+
+impl<J: GraphicsJob> JobInput<J> for JobIndirectBuffer {
+    type Data = Read<JobIndirectBuffer>;
+
+    type Item<'a> = &'a Buffer;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<ExtractComponentPlugin<JobIndirectBuffer>>() {
+                app.add_plugins(ExtractComponentPlugin::<JobIndirectBuffer>::default());
+            }
+        }
+    }
+
+    fn status(data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        if data.0.usage().contains(BufferUsages::INDIRECT) {
+            JobInputStatus::Ready
+        } else {
+            error!(
+                "the indirect buffer of a `{}` job lacks `INDIRECT` usage",
+                J::label()
+            );
+            JobInputStatus::Fail
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.0
+    }
+}
+
+impl ExtractComponent for JobIndirectBuffer {
+    type QueryData = Read<JobIndirectBuffer>;
+
+    type QueryFilter = ();
+
+    type Out = JobIndirectBuffer;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// A [`JobInput`] type that yields a variable number of other entities, for jobs that
+/// gather data from several sources, like all visible terrain chunks. This component
+/// must be added to a job as it is spawned, and is re-extracted whenever it changes.
+///
+/// The listed entities are main-world entities, which must be synced to the render
+/// world, for example with [`SyncToRenderWorld`](bevy_render::sync_world::SyncToRenderWorld).
+/// Jobs fail if any of them aren't. The render-world entity of each is yielded, in order.
+/// To read a component on each entity instead, see [`JobEntitiesWith`].
+#[derive(Component, Clone, Default, Debug)]
+pub struct JobEntities(pub Vec<Entity>);
+
+/// The render-world entities listed by a job's [`JobEntities`], or `None` for
+/// entities that aren't synced to the render world.
+#[derive(Component)]
+#[doc(hidden)]
+pub struct RenderJobEntities(Vec<Option<Entity>>);
+
+impl<J: GraphicsJob> JobInput<J> for JobEntities {
+    type Data = Read<RenderJobEntities>;
+
+    type Item<'a> = Vec<EntityRef<'a>>;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<JobEntitiesPlugin>() {
+                app.add_plugins(JobEntitiesPlugin);
+            }
+        }
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        job_entities_status(data, world, |_| true)
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        data.0
+            .iter()
+            .flatten()
+            .map(|&entity| world.entity(entity))
+            .collect()
+    }
+}
+
+/// A [`JobInput`] type that yields the component `T` on each entity listed by a job's
+/// [`JobEntities`], in order. Jobs wait until every entity has the component, which
+/// must be extracted to the render world separately, for example with an
+/// [`ExtractComponentPlugin`].
+pub struct JobEntitiesWith<T>(PhantomData<T>);
+
+impl<J: GraphicsJob, T: Component> JobInput<J> for JobEntitiesWith<T> {
+    type Data = Read<RenderJobEntities>;
+
+    type Item<'a> = Vec<&'a T>;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<JobEntitiesPlugin>() {
+                app.add_plugins(JobEntitiesPlugin);
+            }
+        }
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        job_entities_status(data, world, |entity| entity.contains::<T>())
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        data.0
+            .iter()
+            .flatten()
+            .map(|&entity| {
+                world
+                    .get::<T>(entity)
+                    .expect("component should be present by this point")
+            })
+            .collect()
+    }
+}
+
+/// Fails if any listed entity isn't synced to the render world, and waits until
+/// every listed entity exists and matches `ready`.
+fn job_entities_status(
+    entities: &RenderJobEntities,
+    world: &World,
+    ready: impl Fn(EntityRef) -> bool,
+) -> JobInputStatus {
+    let mut status = JobInputStatus::Ready;
+    for entity in &entities.0 {
+        let Some(entity) = entity else {
+            return JobInputStatus::Fail;
+        };
+        if !world.get_entity(*entity).is_ok_and(&ready) {
+            status = JobInputStatus::Wait;
+        }
+    }
+    status
+}
+
+struct JobEntitiesPlugin;
+
+impl Plugin for JobEntitiesPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(ExtractSchedule, extract_job_entities);
+        }
+    }
+}
+
+fn extract_job_entities(
+    jobs: Extract<Query<(RenderEntity, &JobEntities), (With<JobMarker>, Changed<JobEntities>)>>,
+    render_entities: Extract<Query<&RenderEntity>>,
+    mut commands: Commands,
+) {
+    let extracted = jobs
+        .iter()
+        .map(|(render_entity, entities)| {
+            let entities = entities
+                .0
+                .iter()
+                .map(|&entity| render_entities.get(entity).ok().map(RenderEntity::id))
+                .collect();
+            (render_entity, RenderJobEntities(entities))
+        })
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(extracted);
+}
+
+/// A [`JobInput`] type that yields the view of a camera, for jobs that depend on it,
+/// such as screen-space effects. The component holds the main-world camera entity.
+/// Jobs wait until the camera's view uniforms are prepared, which happens each frame
+/// the camera is active, and fail if the entity isn't synced to the render world.
+/// The view is re-resolved whenever this component changes.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct JobView(pub Entity);
+
+/// The render-world camera entity of a job's [`JobView`], or `None` if it isn't
+/// synced to the render world.
+#[derive(Component)]
+#[doc(hidden)]
+pub struct RenderJobView(Option<Entity>);
+
+/// A camera's view, as provided by [`JobView`].
+pub struct JobViewItem<'a> {
+    /// The render-world camera entity
+    pub entity: Entity,
+    pub view: &'a ExtractedView,
+    /// The offset of this view's uniforms in `uniforms`, to be passed as the
+    /// dynamic offset when binding them
+    pub uniform_offset: &'a ViewUniformOffset,
+    /// The buffer of view uniforms for all views, for binding as a dynamic uniform
+    pub uniforms: BindingResource<'a>,
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobView {
+    type Data = Read<RenderJobView>;
+
+    type Item<'a> = JobViewItem<'a>;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<JobViewPlugin>() {
+                app.add_plugins(JobViewPlugin);
+            }
+        }
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let Some(entity) = data.0 else {
+            return JobInputStatus::Fail;
+        };
+
+        let view_prepared = world.get_entity(entity).is_ok_and(|view| {
+            view.contains::<ExtractedView>() && view.contains::<ViewUniformOffset>()
+        });
+        let uniforms_prepared = world
+            .get_resource::<ViewUniforms>()
+            .is_some_and(|uniforms| uniforms.uniforms.binding().is_some());
+
+        if view_prepared && uniforms_prepared {
+            JobInputStatus::Ready
+        } else {
+            JobInputStatus::Wait
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        let entity = data.0.expect("view should be ready by this point");
+        let view = world.entity(entity);
+        JobViewItem {
+            entity,
+            view: view.get().expect("view should be ready by this point"),
+            uniform_offset: view.get().expect("view should be ready by this point"),
+            uniforms: world
+                .resource::<ViewUniforms>()
+                .uniforms
+                .binding()
+                .expect("view uniforms should be ready by this point"),
+        }
+    }
+}
+
+struct JobViewPlugin;
+
+impl Plugin for JobViewPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(ExtractSchedule, extract_job_views);
+        }
+    }
+}
+
+fn extract_job_views(
+    jobs: Extract<Query<(RenderEntity, &JobView), (With<JobMarker>, Changed<JobView>)>>,
+    render_entities: Extract<Query<&RenderEntity>>,
+    mut commands: Commands,
+) {
+    let extracted = jobs
+        .iter()
+        .map(|(render_entity, view)| {
+            let view = render_entities.get(view.0).ok().map(RenderEntity::id);
+            (render_entity, RenderJobView(view))
+        })
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(extracted);
+}
+
+/// A [`JobInput`] type that waits for an asset to be prepared by the render world as
+/// the [`RenderAsset`] `R`, and yields it. For example, `JobAsset<GpuImage>` waits
+/// for an [`Image`] to be uploaded to the GPU. Jobs fail
+/// with [`JobError::InputsFailed`](crate::JobError::InputsFailed) if the asset fails
+/// to load. This component must be added to a job as it is spawned.
+#[derive(Component)]
+pub struct JobAsset<R: RenderAsset>(pub Handle<R::SourceAsset>);
+
+impl<R: RenderAsset> Clone for JobAsset<R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Marks a job whose [`JobAsset`] failed to load in the main world
+#[derive(Component)]
+#[doc(hidden)]
+pub struct JobAssetLoadFailed<R>(PhantomData<R>);
+
+impl<J: GraphicsJob, R: RenderAsset> JobInput<J> for JobAsset<R> {
+    type Data = (Read<JobAsset<R>>, Has<JobAssetLoadFailed<R>>);
+
+    type Item<'a> = &'a R;
+
+    fn plugin() -> impl Plugin {
+        JobAssetPlugin::<J, R>(PhantomData)
+    }
+
+    fn status((asset, load_failed): QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        if load_failed {
+            return JobInputStatus::Fail;
+        }
+
+        let Some(render_assets) = world.get_resource::<RenderAssets<R>>() else {
+            return JobInputStatus::Fail;
+        };
+
+        match render_assets.get(asset.0.id()) {
+            Some(_) => JobInputStatus::Ready,
+            None => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>((asset, _): QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        world
+            .resource::<RenderAssets<R>>()
+            .get(asset.0.id())
+            .expect("asset should be prepared by this point")
+    }
+}
+
+impl<R: RenderAsset> ExtractComponent for JobAsset<R> {
+    type QueryData = Read<JobAsset<R>>;
+
+    type QueryFilter = ();
+
+    type Out = JobAsset<R>;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+struct JobAssetPlugin<J, R>(PhantomData<(J, R)>);
+
+impl<J: GraphicsJob, R: RenderAsset> Plugin for JobAssetPlugin<J, R> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobAsset<R>>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobAsset<R>>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(ExtractSchedule, extract_job_asset_load_failures::<R>);
+        }
+    }
+}
+
+fn extract_job_asset_load_failures<R: RenderAsset>(
+    jobs: Extract<Query<(RenderEntity, &JobAsset<R>)>>,
+    asset_server: Extract<Option<Res<AssetServer>>>,
+    mut commands: Commands,
+) {
+    let Some(asset_server) = asset_server.as_ref() else {
+        return;
+    };
+
+    let failed = jobs
+        .iter()
+        .filter(|(_, asset)| {
+            matches!(
+                asset_server.get_load_state(asset.0.id()),
+                Some(LoadState::Failed(_))
+            )
+        })
+        .map(|(render_entity, _)| (render_entity, JobAssetLoadFailed::<R>(PhantomData)))
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(failed);
+}
+
+/// A [`JobInput`] type that waits for a [`ShaderStorageBuffer`] to be uploaded to the
+/// GPU, and yields its [`Buffer`], for example to copy between buffers in `run`. This
+/// component must be added to a job as it is spawned.
+///
+/// Jobs fail with [`JobError::InputsFailed`](crate::JobError::InputsFailed), and an
+/// error is logged, if the asset is removed from the main world before the job runs,
+/// or fails to load.
+#[derive(Component, Clone, Debug)]
+pub struct JobStorageBuffer(pub Handle<ShaderStorageBuffer>);
+
+/// Marks a job whose [`JobStorageBuffer`] no longer exists in the main world
+#[derive(Component)]
+#[doc(hidden)]
+pub struct JobStorageBufferMissing;
+
+impl<J: GraphicsJob> JobInput<J> for JobStorageBuffer {
+    type Data = (Read<JobStorageBuffer>, Has<JobStorageBufferMissing>);
+
+    type Item<'a> = &'a Buffer;
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if app.is_plugin_added::<ExtractComponentPlugin<JobStorageBuffer>>() {
+                return;
+            }
+
+            app.add_plugins(ExtractComponentPlugin::<JobStorageBuffer>::default());
+
+            if let Some(render_app) = job_sub_app_mut(app) {
+                render_app.add_systems(ExtractSchedule, extract_missing_job_storage_buffers);
+            }
+        }
+    }
+
+    fn status((buffer, missing): QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        if missing {
+            error!(
+                "the storage buffer `{:?}` of a `{}` job was removed or failed to load",
+                buffer.0.id(),
+                J::label()
+            );
+            return JobInputStatus::Fail;
+        }
+
+        let Some(buffers) = world.get_resource::<RenderAssets<GpuShaderStorageBuffer>>() else {
+            return JobInputStatus::Fail;
+        };
+
+        match buffers.get(buffer.0.id()) {
+            Some(_) => JobInputStatus::Ready,
+            None => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>((buffer, _): QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        &world
+            .resource::<RenderAssets<GpuShaderStorageBuffer>>()
+            .get(buffer.0.id())
+            .expect("storage buffer should be uploaded by this point")
+            .buffer
+    }
+}
+
+impl ExtractComponent for JobStorageBuffer {
+    type QueryData = Read<JobStorageBuffer>;
+
+    type QueryFilter = ();
+
+    type Out = JobStorageBuffer;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+fn extract_missing_job_storage_buffers(
+    jobs: Extract<Query<(RenderEntity, &JobStorageBuffer), With<JobMarker>>>,
+    buffers: Extract<Option<Res<Assets<ShaderStorageBuffer>>>>,
+    asset_server: Extract<Option<Res<AssetServer>>>,
+    mut commands: Commands,
+) {
+    let Some(buffers) = buffers.as_ref() else {
+        return;
+    };
+
+    let missing = jobs
+        .iter()
+        .filter(|(_, buffer)| {
+            let load_state = asset_server
+                .as_ref()
+                .and_then(|asset_server| asset_server.get_load_state(buffer.0.id()));
+            match load_state {
+                Some(LoadState::Failed(_)) => true,
+                Some(LoadState::Loading) => false,
+                _ => !buffers.contains(buffer.0.id()),
+            }
+        })
+        .map(|(render_entity, _)| (render_entity, JobStorageBufferMissing))
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(missing);
+}
+
+/// A [`JobInput`] type that encodes a value as push constants, and yields the bytes to
+/// pass to `set_push_constants`. The value is encoded with the same layout as storage
+/// buffers, and re-encoded whenever it changes. This component must be added to a job
+/// as it is spawned.
+///
+/// Jobs fail with [`JobError::InputsFailed`](crate::JobError::InputsFailed), and an
+/// error is logged, if the render device doesn't support push constants, or the value
+/// is larger than its `max_push_constant_size` limit. The pipeline layout must also
+/// declare a matching push constant range.
+#[derive(Component, Clone, Debug)]
+pub struct JobPushConstants<T: ShaderType + WriteInto + Send + Sync + 'static>(pub T);
+
+/// The encoded bytes of a [`JobPushConstants`]
+#[derive(Component)]
+#[doc(hidden)]
+pub struct JobPushConstantBytes<T>(Vec<u8>, PhantomData<T>);
+
+impl<J: GraphicsJob, T: ShaderType + WriteInto + Send + Sync + 'static> JobInput<J>
+    for JobPushConstants<T>
+{
+    type Data = Read<JobPushConstantBytes<T>>;
+
+    type Item<'a> = &'a [u8];
+
+    fn plugin() -> impl Plugin {
+        |app: &mut App| {
+            if !app.is_plugin_added::<ExtractComponentPlugin<JobPushConstants<T>>>() {
+                app.add_plugins(ExtractComponentPlugin::<JobPushConstants<T>>::default());
+            }
+        }
+    }
+
+    fn status(bytes: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let Some(render_device) = world.get_resource::<RenderDevice>() else {
+            return JobInputStatus::Fail;
+        };
+
+        if !render_device.features().contains(Features::PUSH_CONSTANTS) {
+            error!(
+                "a `{}` job uses push constants, which the render device doesn't support",
+                J::label()
+            );
+            return JobInputStatus::Fail;
+        }
+
+        let max_size = render_device.limits().max_push_constant_size;
+        if bytes.0.len() > max_size as usize {
+            error!(
+                "the push constants of a `{}` job are {} bytes, over the limit of {max_size}",
+                J::label(),
+                bytes.0.len()
+            );
+            return JobInputStatus::Fail;
+        }
+
+        JobInputStatus::Ready
+    }
+
+    fn get<'a>(bytes: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &bytes.0
+    }
+}
+
+impl<T: ShaderType + WriteInto + Send + Sync + 'static> ExtractComponent for JobPushConstants<T> {
+    type QueryData = Read<JobPushConstants<T>>;
+
+    type QueryFilter = ();
+
+    type Out = JobPushConstantBytes<T>;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        let mut buffer = StorageBuffer::new(Vec::new());
+        buffer.write(&item.0).ok()?;
+        Some(JobPushConstantBytes(buffer.into_inner(), PhantomData))
+    }
+}
+
+/// A [`JobInput`] type that provides a pair of ping-pong buffers, so a job can read
+/// the output written by the previous job with the same `key` while writing its own.
+/// This component must be added to a job as it is spawned.
+///
+/// After each successful run, the buffers are swapped, so that the buffer written
+/// this time is yielded as `previous` next time. This is useful for temporal
+/// algorithms, where a job is spawned each frame to accumulate results over time.
+///
+/// Note: history buffers are kept for the lifetime of the app. If `size` or
+/// `usage` changes for a key, the buffers are recreated and their contents lost.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct JobPreviousOutput {
+    /// Identifies the history shared between jobs
+    pub key: u64,
+    /// The size of each buffer, in bytes
+    pub size: u64,
+    /// The usages of each buffer
+    pub usage: BufferUsages,
+}
+
+/// The buffers provided by [`JobPreviousOutput`].
+pub struct JobHistoryBuffers<'a> {
+    /// The buffer written by the last job with the same key
+    pub previous: &'a Buffer,
+    /// The buffer the current job should write to
+    pub current: &'a Buffer,
+}
+
+struct HistoryBuffers {
+    buffers: [Buffer; 2],
+    size: u64,
+    usage: BufferUsages,
+    flipped: AtomicBool,
+}
+
+impl HistoryBuffers {
+    fn get(&self) -> JobHistoryBuffers<'_> {
+        let flipped = self.flipped.load(Ordering::Acquire) as usize;
+        JobHistoryBuffers {
+            previous: &self.buffers[1 - flipped],
+            current: &self.buffers[flipped],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct JobHistory(HashMap<u64, HistoryBuffers>);
+
+impl<J: GraphicsJob> JobInput<J> for JobPreviousOutput {
+    type Data = Read<JobPreviousOutput>;
+
+    type Item<'a> = JobHistoryBuffers<'a>;
+
+    fn plugin() -> impl Plugin {
+        JobPreviousOutputPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        match world.resource::<JobHistory>().0.get(&data.key) {
+            Some(history) if history.size == data.size && history.usage == data.usage => {
+                JobInputStatus::Ready
+            }
+            _ => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, world: &'a World) -> Self::Item<'a> {
+        world
+            .resource::<JobHistory>()
+            .0
+            .get(&data.key)
+            .expect("history buffers should be ready by this point")
+            .get()
+    }
+
+    fn post_run(data: QueryItem<Self::Data>, world: &World, _command_encoder: &mut CommandEncoder) {
+        if let Some(history) = world.resource::<JobHistory>().0.get(&data.key) {
+            history.flipped.fetch_xor(true, Ordering::AcqRel);
+        }
+    }
+}
+
+impl ExtractComponent for JobPreviousOutput {
+    type QueryData = Read<JobPreviousOutput>;
+
+    type QueryFilter = ();
+
+    type Out = JobPreviousOutput;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(*item)
+    }
+}
+
+struct JobPreviousOutputPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobPreviousOutputPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobPreviousOutput>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobPreviousOutput>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.init_resource::<JobHistory>().add_systems(
+                Render,
+                prepare_job_history.in_set(RenderSet::PrepareResources),
+            );
+        }
+    }
+}
+
+fn prepare_job_history(
+    jobs: Query<&JobPreviousOutput>,
+    render_device: Res<RenderDevice>,
+    mut history: ResMut<JobHistory>,
+) {
+    for job in &jobs {
+        if history
+            .0
+            .get(&job.key)
+            .is_some_and(|history| history.size == job.size && history.usage == job.usage)
+        {
+            continue;
+        }
+
+        let create_buffer = || {
+            render_device.create_buffer(&BufferDescriptor {
+                label: Some("job_history_buffer"),
+                size: job.size,
+                usage: job.usage,
+                mapped_at_creation: false,
+            })
+        };
+
+        history.0.insert(
+            job.key,
+            HistoryBuffers {
+                buffers: [create_buffer(), create_buffer()],
+                size: job.size,
+                usage: job.usage,
+                flipped: AtomicBool::new(false),
+            },
+        );
+    }
+}
+
+/// A [`JobInput`] type that provides a job with a temporary buffer, taken from a pool
+/// of recycled buffers. This component must be added to a job as it is spawned.
+///
+/// The buffer stays with the job for as long as its render-world entity exists, so
+/// repeated runs of the same job, as with [`JobRepeat`](crate::meta::JobRepeat),
+/// [`StandingJob`](crate::meta::StandingJob) or [`JobTimeSlice`], keep the same buffer.
+/// Once the job completes and its entity is despawned, the buffer is returned to the
+/// pool, to be reused by later jobs with the same `size` and `usage`. Recycled buffers
+/// aren't cleared, so jobs shouldn't rely on their initial contents.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct JobScratchBuffer {
+    /// The size of the buffer, in bytes
+    pub size: u64,
+    /// The usages of the buffer
+    pub usage: BufferUsages,
+}
+
+#[derive(Component)]
+#[component(on_remove = return_job_scratch_buffer)]
+#[doc(hidden)]
+pub struct PreparedJobScratchBuffer(Buffer, u64, BufferUsages);
+
+/// Returns a job's scratch buffer to the pool once it's removed, usually as the job
+/// entity is despawned after completing
+fn return_job_scratch_buffer(world: DeferredWorld, entity: Entity, _component: ComponentId) {
+    let Some(PreparedJobScratchBuffer(buffer, size, usage)) = world.get(entity) else {
+        return;
+    };
+    if let Some(pool) = world.get_resource::<ScratchBufferPool>() {
+        pool.returned
+            .lock()
+            .unwrap()
+            .push((*size, *usage, buffer.clone()));
+    }
+}
+
+/// Scratch buffers not currently in use by any job, keyed by size and usage.
+#[derive(Resource, Default)]
+struct ScratchBufferPool {
+    free: HashMap<(u64, BufferUsages), Vec<Buffer>>,
+    returned: Mutex<Vec<(u64, BufferUsages, Buffer)>>,
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobScratchBuffer {
+    type Data = (
+        Read<JobScratchBuffer>,
+        Option<Read<PreparedJobScratchBuffer>>,
+    );
+
+    type Item<'a> = &'a Buffer;
+
+    fn plugin() -> impl Plugin {
+        JobScratchBufferPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        match data.1 {
+            Some(_) => JobInputStatus::Ready,
+            None => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.1.unwrap().0
+    }
+}
+
+impl ExtractComponent for JobScratchBuffer {
+    type QueryData = Read<JobScratchBuffer>;
+
+    type QueryFilter = ();
+
+    type Out = JobScratchBuffer;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(*item)
+    }
+}
+
+struct JobScratchBufferPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobScratchBufferPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobScratchBuffer>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobScratchBuffer>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.init_resource::<ScratchBufferPool>().add_systems(
+                Render,
+                (
+                    prepare_job_scratch_buffers.in_set(RenderSet::PrepareResources),
+                    recycle_job_scratch_buffers.in_set(RenderSet::Cleanup),
+                ),
+            );
+        }
+    }
+}
+
+fn prepare_job_scratch_buffers(
+    jobs: Query<(Entity, &JobScratchBuffer), Without<PreparedJobScratchBuffer>>,
+    render_device: Res<RenderDevice>,
+    mut pool: ResMut<ScratchBufferPool>,
+    mut commands: Commands,
+) {
+    for (entity, scratch) in &jobs {
+        let buffer = pool
+            .free
+            .get_mut(&(scratch.size, scratch.usage))
+            .and_then(Vec::pop)
+            .unwrap_or_else(|| {
+                render_device.create_buffer(&BufferDescriptor {
+                    label: Some("job_scratch_buffer"),
+                    size: scratch.size,
+                    usage: scratch.usage,
+                    mapped_at_creation: false,
+                })
+            });
+
+        commands.entity(entity).insert(PreparedJobScratchBuffer(
+            buffer,
+            scratch.size,
+            scratch.usage,
+        ));
+    }
+}
+
+/// Makes the scratch buffers of jobs despawned this frame available again. Since jobs
+/// are submitted before [`RenderSet::Cleanup`], later uses are ordered after this frame's.
+fn recycle_job_scratch_buffers(mut pool: ResMut<ScratchBufferPool>) {
+    let ScratchBufferPool { free, returned } = &mut *pool;
+    for (size, usage, buffer) in returned.get_mut().unwrap().drain(..) {
+        free.entry((size, usage)).or_default().push(buffer);
+    }
+}
+
+/// A [`JobInput`] type that allows a long-running job to spread its work over several
+/// frames. This component must be added to a job as it is spawned, with the time the
+/// job should aim to spend encoding each frame.
+///
+/// The job is given a [`TimeSlice`], and may call [`TimeSlice::continue_next_frame`]
+/// once its budget is exhausted, in which case the commands encoded so far are submitted,
+/// and the job runs again next frame instead of completing. Jobs must track their own
+/// progress between slices, for example with [`TimeSlice::index`]. Note that [`JobInput::post_run`]
+/// is called for every slice, and the job's time-out is reset each time it yields.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct JobTimeSlice(pub Duration);
+
+/// The time budget for a single slice of a job, as provided by [`JobTimeSlice`].
+pub struct TimeSlice<'a> {
+    budget: Duration,
+    start: Instant,
+    index: u32,
+    continuation: &'a JobContinuation,
+}
+
+impl TimeSlice<'_> {
+    /// The number of slices this job has already run for
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The time remaining in this slice's budget
+    pub fn remaining(&self) -> Duration {
+        self.budget.saturating_sub(self.start.elapsed())
+    }
+
+    /// Whether this slice's budget has been used up
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Marks the job as unfinished, so it runs again next frame rather than completing.
+    /// The job must still return `Ok(())` for its commands to be submitted.
+    pub fn continue_next_frame(&self) {
+        self.continuation.yielded.store(true, Ordering::Release);
+    }
+}
+
+#[derive(Component, Default)]
+#[doc(hidden)]
+pub struct JobContinuation {
+    yielded: AtomicBool,
+    slices: AtomicU32,
+}
+
+impl JobContinuation {
+    /// Whether the job hasn't run any slices yet.
+    pub(crate) fn is_first_slice(&self) -> bool {
+        self.slices.load(Ordering::Acquire) == 0
+    }
+
+    /// Returns whether the job yielded during its last slice, resetting the flag.
+    pub(crate) fn take_yielded(&self) -> bool {
+        self.slices.fetch_add(1, Ordering::AcqRel);
+        self.yielded.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobTimeSlice {
+    type Data = (Read<JobTimeSlice>, Option<Read<JobContinuation>>);
+
+    type Item<'a> = TimeSlice<'a>;
+
+    fn plugin() -> impl Plugin {
+        JobTimeSlicePlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+        match data.1 {
+            Some(_) => JobInputStatus::Ready,
+            None => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        let (time_slice, continuation) = data;
+        let continuation = continuation.unwrap();
+        TimeSlice {
+            budget: time_slice.0,
+            start: Instant::now(),
+            index: continuation.slices.load(Ordering::Acquire),
+            continuation,
+        }
+    }
+}
+
+impl ExtractComponent for JobTimeSlice {
+    type QueryData = Read<JobTimeSlice>;
+
+    type QueryFilter = ();
+
+    type Out = JobTimeSlice;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(*item)
+    }
+}
+
+struct JobTimeSlicePlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobTimeSlicePlugin<J> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobTimeSlice>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobTimeSlice>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(
+                Render,
+                prepare_job_continuations.in_set(RenderSet::PrepareResources),
+            );
+        }
+    }
+}
+
+fn prepare_job_continuations(
+    jobs: Query<Entity, (With<JobTimeSlice>, Without<JobContinuation>)>,
+    mut commands: Commands,
+) {
+    let to_insert = jobs
+        .iter()
+        .map(|entity| (entity, JobContinuation::default()))
+        .collect::<Vec<_>>();
+    commands.insert_batch(to_insert);
+}
+
+#[cfg(test)]
+mod test {
+    use core::marker::PhantomData;
+
+    use bevy_ecs::{
+        component::Component,
+        schedule::Schedule,
+        system::{lifetimeless::SRes, Resource, SystemParamItem},
+        world::World,
+    };
+    use bevy_render::{
+        render_resource::{
+            AsBindGroup, AsBindGroupError, BindGroupLayout, BindGroupLayoutEntry, BindingType,
+            Buffer, BufferBindingType, BufferDescriptor, BufferUsages, CachedPipelineState,
+            CommandEncoder, OwnedBindingResource, PipelineCacheError, Shader, ShaderStages,
+            UnpreparedBindGroup,
+        },
+        renderer::RenderDevice,
+    };
+    use naga_oil::compose::{Composer, NagaModuleDescriptor};
+
+    use super::{
+        inner_status, pipeline_status, prepare_job_bind_group, Either, EitherItem, JobBindGroup,
+        JobBindGroupFailed, JobBindGroupLayout, JobInput, JobInputItem, JobInputStatus,
+        JobResource, Opt, PreparedJobBindGroup,
+    };
+    use crate::{GraphicsJob, JobError};
+
+    #[derive(Component)]
+    struct TestJob;
+
+    impl GraphicsJob for TestJob {
+        type In = ();
+
+        fn run(
+            &self,
+            _world: &World,
+            _render_device: &RenderDevice,
+            _command_encoder: &mut CommandEncoder,
+            _input: JobInputItem<Self, Self::In>,
+        ) -> Result<(), JobError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Resource)]
+    struct First;
+
+    #[derive(Resource)]
+    struct Second;
+
+    type FirstOrSecond = Either<JobResource<First>, JobResource<Second>>;
+
+    #[test]
+    fn either_is_ready_when_one_side_is() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let status = <FirstOrSecond as JobInput<TestJob>>::status(world.entity(entity), &world);
+        assert_eq!(status, JobInputStatus::Wait);
+
+        world.insert_resource(Second);
+        let status = <FirstOrSecond as JobInput<TestJob>>::status(world.entity(entity), &world);
+        assert_eq!(status, JobInputStatus::Ready);
+        let item = <FirstOrSecond as JobInput<TestJob>>::get(world.entity(entity), &world);
+        assert!(matches!(item, EitherItem::Right(_)));
+
+        // the left side is preferred when both are ready
+        world.insert_resource(First);
+        let item = <FirstOrSecond as JobInput<TestJob>>::get(world.entity(entity), &world);
+        assert!(matches!(item, EitherItem::Left(_)));
+    }
+
+    #[derive(Component)]
+    struct Hint(u32);
+
+    #[test]
+    fn missing_optional_input_is_none() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let status = <Opt<&Hint> as JobInput<TestJob>>::status(world.entity(entity), &world);
+        assert_eq!(status, JobInputStatus::Ready);
+        let item = <Opt<&Hint> as JobInput<TestJob>>::get(world.entity(entity), &world);
+        assert!(item.is_none());
+
+        world.entity_mut(entity).insert(Hint(3));
+        let item = <Opt<&Hint> as JobInput<TestJob>>::get(world.entity(entity), &world);
+        assert_eq!(item.map(|hint| hint.0), Some(3));
+    }
+
+    /// A render device for tests that need real GPU resources, or `None` if no adapter
+    /// is available, in which case those tests are skipped
+    fn test_render_device() -> Option<RenderDevice> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default()))?;
+        let (device, _queue) =
+            pollster::block_on(adapter.request_device(&Default::default(), None)).ok()?;
+        Some(RenderDevice::from(device))
+    }
+
+    /// The storage buffer bound by `TestBindGroup`, which may become available late
+    #[derive(Resource, Default)]
+    struct TestStorageBuffer(Option<Buffer>);
+
+    #[derive(Component, Clone)]
+    struct TestBindGroup;
+
+    impl AsBindGroup for TestBindGroup {
+        type Data = ();
+
+        type Param = SRes<TestStorageBuffer>;
+
+        fn unprepared_bind_group(
+            &self,
+            _layout: &BindGroupLayout,
+            _render_device: &RenderDevice,
+            storage_buffer: &mut SystemParamItem<'_, '_, Self::Param>,
+        ) -> Result<UnpreparedBindGroup<Self::Data>, AsBindGroupError> {
+            let buffer = storage_buffer
+                .0
+                .clone()
+                .ok_or(AsBindGroupError::RetryNextUpdate)?;
+            Ok(UnpreparedBindGroup {
+                bindings: vec![(0, OwnedBindingResource::Buffer(buffer))],
+                data: (),
+            })
+        }
+
+        fn bind_group_layout_entries(_render_device: &RenderDevice) -> Vec<BindGroupLayoutEntry> {
+            vec![BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }]
+        }
+    }
+
+    type TestBindGroupInput = JobBindGroup<0, TestBindGroup>;
+
+    #[test]
+    fn bind_group_waits_until_prepared() {
+        let mut world = World::new();
+        // as `JobBindGroupPlugin` does, since `get_components` fails for unregistered components
+        world.register_component::<PreparedJobBindGroup<TestBindGroup>>();
+        let failed = world
+            .spawn((
+                TestBindGroup,
+                JobBindGroupFailed::<TestBindGroup>(PhantomData),
+            ))
+            .id();
+        let status = inner_status::<TestJob, TestBindGroupInput>(&world.entity(failed), &world);
+        assert_eq!(status, JobInputStatus::Fail);
+
+        let Some(render_device) = test_render_device() else {
+            eprintln!("skipping the rest of `bind_group_waits_until_prepared`: no GPU adapter");
+            return;
+        };
+        world.insert_resource(JobBindGroupLayout::<TestBindGroup>(
+            TestBindGroup::bind_group_layout(&render_device),
+            PhantomData,
+        ));
+        world.insert_resource(render_device.clone());
+        world.init_resource::<TestStorageBuffer>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(prepare_job_bind_group::<TestBindGroup>);
+
+        let entity = world.spawn(TestBindGroup).id();
+        let status = |world: &World| {
+            inner_status::<TestJob, TestBindGroupInput>(&world.entity(entity), world)
+        };
+
+        // the storage buffer isn't ready, so preparing the bind group is retried each frame
+        for _ in 0..2 {
+            schedule.run(&mut world);
+            assert_eq!(status(&world), JobInputStatus::Wait);
+        }
+
+        world.resource_mut::<TestStorageBuffer>().0 =
+            Some(render_device.create_buffer(&BufferDescriptor {
+                label: None,
+                size: 16,
+                usage: BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }));
+        schedule.run(&mut world);
+        assert_eq!(status(&world), JobInputStatus::Ready);
+    }
+
+    #[test]
+    fn broken_pipelines_fail() {
+        // a shader with a typo, composed the way the pipeline cache does
+        let shader = Shader::from_wgsl(
+            "@compute @workgroup_size(1) fn main() { let x = ; }",
+            "broken.wgsl",
+        );
+        let error = Composer::default()
+            .make_naga_module(NagaModuleDescriptor::from(&shader))
+            .expect_err("the shader has a syntax error");
+        let broken = CachedPipelineState::Err(PipelineCacheError::ProcessShaderError(error));
+        assert_eq!(pipeline_status(&broken, false), JobInputStatus::Fail);
+        // the next fallback variant is queued instead
+        assert_eq!(pipeline_status(&broken, true), JobInputStatus::Wait);
+
+        let loading = CachedPipelineState::Err(PipelineCacheError::ShaderImportNotYetAvailable);
+        assert_eq!(pipeline_status(&loading, false), JobInputStatus::Wait);
+        assert_eq!(
+            pipeline_status(&CachedPipelineState::Queued, false),
+            JobInputStatus::Wait
+        );
     }
 }
@@ -0,0 +1,172 @@
+//! Measurement of the latency between spawning a job and its completion on the GPU.
+
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::Event,
+    query::{Added, With},
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource},
+};
+use bevy_render::{
+    renderer::RenderQueue,
+    sync_world::{MainEntity, RenderEntity},
+    Extract, ExtractSchedule, Render,
+};
+use crossbeam_channel::{Receiver, Sender};
+
+use crate::{
+    job_sub_app_mut, meta::JobMarker, JobCleanupSet, JobExecutionSettings, JobSubmitHooks,
+};
+
+/// Opts a job into latency measurement. Once the job's commands have finished executing
+/// on the GPU, a [`JobLatency`] event is triggered in the main world. This component must
+/// be added to a job as it is spawned.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct MeasureJobLatency;
+
+/// A global event describing the latency of a job with [`MeasureJobLatency`], in frames
+/// counted by the render world. Since the GPU finishes some time after the job completes,
+/// the job entity will usually be despawned by the time this event is triggered.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct JobLatency {
+    /// The main-world job entity
+    pub entity: Entity,
+    /// The frame the job was extracted to the render world
+    pub spawn_frame: u64,
+    /// The frame the job's commands were encoded and submitted
+    pub execute_frame: u64,
+    /// The first frame after the GPU finished executing the job's commands
+    pub complete_frame: u64,
+}
+
+/// The frame a job with [`MeasureJobLatency`] was extracted
+#[derive(Component, Copy, Clone)]
+struct LatencySpawnFrame(u64);
+
+/// Counts frames in the render world
+#[derive(Resource, Default)]
+struct JobFrameCount(u64);
+
+struct SubmittedLatency {
+    main_entity: MainEntity,
+    spawn_frame: u64,
+    execute_frame: u64,
+}
+
+#[derive(Resource)]
+struct LatencyReceiver(Receiver<SubmittedLatency>);
+
+#[derive(Resource)]
+struct LatencyMainWorldSender(Sender<JobLatency>);
+#[derive(Resource)]
+struct LatencyMainWorldReceiver(Receiver<JobLatency>);
+
+pub(crate) struct JobLatencyPlugin;
+
+impl Plugin for JobLatencyPlugin {
+    fn build(&self, app: &mut App) {
+        let (main_sender, main_receiver) = crossbeam_channel::unbounded();
+
+        app.insert_resource(LatencyMainWorldReceiver(main_receiver))
+            .add_systems(Update, sync_job_latencies_main_world);
+
+        let Some(render_app) = job_sub_app_mut(app) else {
+            return;
+        };
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        render_app
+            .insert_resource(LatencyReceiver(receiver))
+            .insert_resource(LatencyMainWorldSender(main_sender))
+            .init_resource::<JobFrameCount>()
+            .add_systems(ExtractSchedule, extract_latency_spawn_frames)
+            .add_systems(Render, sync_job_latencies.in_set(JobCleanupSet::Readbacks));
+
+        // jobs are collected before submission, and a callback registered after it,
+        // so that the callback refers to the submission containing the jobs.
+        let submitted = Arc::new(Mutex::new(Vec::<SubmittedLatency>::new()));
+        let pre_submitted = submitted.clone();
+
+        render_app
+            .world_mut()
+            .get_resource_or_insert_with(JobSubmitHooks::default)
+            .add_pre_submit(move |world, jobs, _command_buffers| {
+                let execute_frame = world.resource::<JobFrameCount>().0;
+                let mut submitted = pre_submitted.lock().unwrap();
+                for job in jobs {
+                    let (Some(spawn_frame), Some(main_entity)) = (
+                        world.get::<LatencySpawnFrame>(job.entity),
+                        world.get::<MainEntity>(job.entity),
+                    ) else {
+                        continue;
+                    };
+
+                    submitted.push(SubmittedLatency {
+                        main_entity: *main_entity,
+                        spawn_frame: spawn_frame.0,
+                        execute_frame,
+                    });
+                }
+            })
+            .add_post_submit(move |world| {
+                let jobs = mem::take(&mut *submitted.lock().unwrap());
+                if jobs.is_empty() {
+                    return;
+                }
+
+                let sender = sender.clone();
+                world
+                    .resource::<RenderQueue>()
+                    .on_submitted_work_done(move || {
+                        for job in jobs {
+                            let _ = sender.send(job);
+                        }
+                    });
+            });
+    }
+}
+
+fn extract_latency_spawn_frames(
+    jobs: Extract<Query<RenderEntity, (Added<JobMarker>, With<MeasureJobLatency>)>>,
+    frame_count: Res<JobFrameCount>,
+    mut commands: Commands,
+) {
+    let to_insert = jobs
+        .iter()
+        .map(|entity| (entity, LatencySpawnFrame(frame_count.0)))
+        .collect::<Vec<_>>();
+    commands.insert_batch(to_insert);
+}
+
+fn sync_job_latencies(
+    receiver: Res<LatencyReceiver>,
+    main_world_sender: Res<LatencyMainWorldSender>,
+    exec_settings: Res<JobExecutionSettings>,
+    mut frame_count: ResMut<JobFrameCount>,
+) {
+    while let Ok(job) = receiver.0.try_recv() {
+        exec_settings
+            .channel_failure_policy
+            .handle(main_world_sender.0.send(JobLatency {
+                entity: job.main_entity.id(),
+                spawn_frame: job.spawn_frame,
+                execute_frame: job.execute_frame,
+                complete_frame: frame_count.0,
+            }));
+    }
+
+    frame_count.0 += 1;
+}
+
+fn sync_job_latencies_main_world(receiver: Res<LatencyMainWorldReceiver>, mut commands: Commands) {
+    while let Ok(latency) = receiver.0.try_recv() {
+        commands.trigger(latency);
+    }
+}
@@ -13,45 +13,110 @@
 //! 2. Add `GraphicsJobsPlugin` to your `App`
 //! 3. Implement `GraphicsJob` for your job component
 //! 4. Call `init_graphics_job` on `App` to initialize your custom job
-//! 5. To run the job, simply spawn an entity with your job component!
+//! 5. To run the job, simply spawn an entity with your job component! `spawn_graphics_job`
+//!    on `Commands` does this with a priority, and optionally a completion observer.
+//!
+//! Note: `GraphicsJobsPlugin` should be added and jobs initialized before any jobs are
+//! spawned. Jobs spawned earlier (for example, in a plugin's `build`) will be picked up
+//! once the app finishes building, with a warning.
 //!
 //! See the examples in the repo for more in-depth showcases!
 
 #![allow(clippy::type_complexity)]
 
+pub mod compute;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
 mod ext;
+pub mod graph;
 pub mod input;
+pub mod latency;
+mod macros;
 pub mod meta;
+pub mod query;
+pub mod readback;
 mod runner;
+pub mod testing;
+pub mod throttle;
+pub mod timing;
+#[cfg(feature = "trace")]
+pub mod trace;
+mod validation;
 use disqualified::ShortName;
 pub use ext::*;
-use input::{JobInput, JobInputItem};
-use meta::{extract_job_meta, JobMarker};
+use input::{JobInput, JobInputItem, JobInputStatus};
+use latency::JobLatencyPlugin;
+use meta::{
+    extract_cancelled_jobs, extract_disabled_jobs, extract_job_meta, JobMarker,
+    JobTypePriorityOverrides, StandingJob,
+};
+use readback::{
+    map_job_readbacks, poll_job_readbacks, sync_job_readbacks_main_world, JobReadbacks,
+    JobsAwaitingReadback, ReadbackMainWorldReceiver, ReadbackMainWorldSender,
+};
 use runner::{
-    check_job_inputs, erase_jobs, increment_time_out_frames, run_jobs, setup_time_out_frames,
-    sync_completed_jobs, sync_completed_jobs_main_world, time_out_jobs, JobResultMainWorldReceiver,
-    JobResultMainWorldSender, JobResultReceiver, JobResultSender, JobSet,
+    advance_stalled_job_frames, cancel_all_jobs, cancel_disabled_job_types, cancel_jobs,
+    cancel_tokened_jobs, check_job_dependencies, check_job_inputs, erase_jobs, extract_app_focus,
+    extract_cancel_all_jobs, extract_flush_all_jobs, fail_jobs_without_sub_app,
+    increment_time_out_frames, limit_in_flight_jobs, process_critical_job_pipelines,
+    propagate_job_priorities, requeue_standing_jobs, resume_graph_jobs, run_jobs,
+    sync_completed_jobs, sync_completed_jobs_main_world, sync_job_input_status_main_world,
+    sync_job_queue_stats_main_world, time_out_jobs, update_job_queue_stats, AppFocus,
+    CompletedJobs, JobQueueStatsMainWorldReceiver, JobQueueStatsMainWorldSender,
+    JobResultMainWorldReceiver, JobResultMainWorldSender, JobResultReceiver, JobResultSender,
+    JobStatusMainWorldReceiver, JobStatusMainWorldSender, JobWaiters, JobsExecutedThisFrame,
+    NextJobSequence, RegisteredJobs, ResumedGraphJobs,
 };
+pub use runner::{JobCleanupSet, JobSet};
+use throttle::{throttle_jobs, JobThrottle};
+use validation::{validate_entry_points, EntryPointValidation};
 
-use core::marker::PhantomData;
+use core::{
+    any::TypeId,
+    fmt,
+    marker::PhantomData,
+    mem::{self, Discriminant},
+};
+use std::borrow::Cow;
 
-use bevy_app::{App, Plugin, Update};
+use bevy_app::{App, AppLabel, InternedAppLabel, Plugin, SubApp, Update};
+use bevy_asset::Handle;
 use bevy_ecs::{
     component::Component,
+    entity::Entity,
     event::Event,
-    query::Added,
-    schedule::{IntoSystemConfigs, IntoSystemSetConfigs},
-    system::{Commands, Query, Resource},
+    query::{Added, Changed, Or, With, Without},
+    schedule::{
+        common_conditions::resource_exists, InternedSystemSet, IntoSystemConfigs,
+        IntoSystemSetConfigs, SystemSet,
+    },
+    system::{Commands, Query, ResMut, Resource},
     world::World,
 };
+use bevy_image::Image;
 use bevy_render::{
     extract_resource::{ExtractResource, ExtractResourcePlugin},
     render_resource::CommandEncoder,
-    renderer::RenderDevice,
+    renderer::{RenderAdapterInfo, RenderDevice},
     sync_component::SyncComponentPlugin,
-    ExtractSchedule, Render, RenderApp, RenderSet,
+    ExtractSchedule, MainWorld, Render, RenderApp, RenderSet,
 };
-use bevy_render::{sync_world::RenderEntity, Extract};
+use bevy_render::{
+    sync_world::{RenderEntity, SyncToRenderWorld},
+    Extract,
+};
+use bevy_utils::{
+    tracing::{error, warn},
+    HashMap, HashSet,
+};
+use crossbeam_channel::{SendError, Sender, TrySendError};
+use wgpu::{AdapterInfo, CommandBuffer, Features, Limits};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use bevy_ecs::world::World;
+    pub use bevy_render::{render_resource::CommandEncoder, renderer::RenderDevice};
+}
 
 /// A trait for components describing a unit of rendering work.
 ///
@@ -60,18 +125,48 @@ use bevy_render::{sync_world::RenderEntity, Extract};
 /// prepared. When they are ready, it will execute and the commands it encodes
 /// will be submitted before the render graph is executed.
 ///
+/// Jobs are extracted during the same frame they're spawned, and may execute that
+/// frame if their inputs are already prepared. Jobs waiting on newly specialized
+/// pipelines usually execute a frame later, once the pipelines have compiled, except
+/// for critical jobs, for which pipelines are processed early.
+///
 /// You can also specify a priority for a running job by adding the [`JobPriority`](meta::JobPriority)
 /// component when it is spawned.
 ///
+/// For jobs that only dispatch a single compute shader, see [`ComputeJob`](compute::ComputeJob).
+/// To cut down on boilerplate otherwise, see [`graphics_job!`].
+///
+/// Jobs are cloned into the render world when they're extracted, so most jobs should
+/// implement [`Clone`]. Jobs that can't, or that are expensive to clone, may instead be
+/// moved into the render world by initializing them with
+/// [`init_moved_graphics_job`](crate::ext::InitGraphicsJobExt::init_moved_graphics_job).
+///
 /// Note: you must call [`init_graphics_job`](crate::ext::InitGraphicsJobExt::init_graphics_job)
 /// on [`App`] for the job to execute.
-pub trait GraphicsJob: Component + Clone {
+pub trait GraphicsJob: Component + Sized {
     type In: JobInput<Self>;
 
     fn label() -> ShortName<'static> {
         ShortName::of::<Self>()
     }
 
+    /// The label of the command encoder a job is encoded into, which is also used
+    /// for its debug group. Since [`label`](GraphicsJob::label) shortens generic
+    /// parameters, generic jobs may override this to tell their instances apart in
+    /// GPU captures. Defaults to the full type name of the job.
+    fn encoder_label(&self) -> Cow<'_, str> {
+        Cow::Borrowed(Self::label().original())
+    }
+
+    /// Encodes the job's commands.
+    ///
+    /// Note on synchronization: `wgpu` doesn't expose memory barriers. Instead, it
+    /// tracks the usage of every buffer and texture, and inserts barriers between
+    /// passes and copies automatically, including between jobs. Within a single
+    /// pass, a resource can't be both written and read through different bindings,
+    /// so jobs that write a buffer and then read it (e.g. compute-then-copy, or
+    /// several dependent dispatches) should end the pass, and begin a new one
+    /// where needed. Sub-ranges of the same buffer are tracked as a whole.
     fn run(
         &self,
         world: &World,
@@ -79,36 +174,289 @@ pub trait GraphicsJob: Component + Clone {
         command_encoder: &mut CommandEncoder,
         input: JobInputItem<Self, Self::In>,
     ) -> Result<(), JobError>;
+
+    /// Encodes the job's commands, with the option of deferring the job to the next
+    /// frame, for conditions that only become visible while encoding. Defaults to
+    /// calling [`run`](GraphicsJob::run).
+    ///
+    /// Jobs that override this aren't run through [`run`](GraphicsJob::run) by `gigs`,
+    /// but `run` may still be called directly, for example by wrapper jobs. Overriders
+    /// should implement it with the same logic, or return an error such as
+    /// `Err(JobError::ExecutionFailed("use run_with_outcome".into()))`, rather than
+    /// panicking.
+    fn run_with_outcome(
+        &self,
+        world: &World,
+        render_device: &RenderDevice,
+        command_encoder: &mut CommandEncoder,
+        input: JobInputItem<Self, Self::In>,
+    ) -> JobRunOutcome {
+        self.run(world, render_device, command_encoder, input)
+            .into()
+    }
+
+    /// Runs in the main world once the job has completed or failed, and after all of
+    /// its readbacks have been delivered, for consuming the job's results without
+    /// a separate observer. Defaults to doing nothing.
+    ///
+    /// The job component is taken from the job entity to run this, and the entity is
+    /// despawned afterwards. Jobs initialized with
+    /// [`init_moved_graphics_job`](InitGraphicsJobExt::init_moved_graphics_job) have
+    /// no job component left in the main world, so this isn't run for them.
+    fn on_complete(&self, _world: &mut World, _output: JobOutput) {}
+}
+
+/// The outcome of running a job with [`GraphicsJob::run_with_outcome`].
+#[derive(Clone, Debug)]
+pub enum JobRunOutcome {
+    /// The job completed successfully, and its commands are submitted.
+    Done,
+    /// The job can't proceed this frame, and runs again next frame. It keeps counting
+    /// frames towards timing out as usual, and [`JobInput::post_run`] isn't called.
+    /// The job's commands are discarded rather than submitted, and [`JobStarted`] is
+    /// triggered again when the job runs next.
+    Defer,
+    /// The job failed, and its commands are discarded, as when [`run`](GraphicsJob::run)
+    /// returns an error.
+    Failed(JobError),
+}
+
+impl JobRunOutcome {
+    /// Converts the outcome into the result of a completed job, or `None` if deferred.
+    pub(crate) fn into_result(self) -> Option<Result<(), JobError>> {
+        match self {
+            JobRunOutcome::Done => Some(Ok(())),
+            JobRunOutcome::Defer => None,
+            JobRunOutcome::Failed(error) => Some(Err(error)),
+        }
+    }
+}
+
+impl From<Result<(), JobError>> for JobRunOutcome {
+    fn from(result: Result<(), JobError>) -> Self {
+        match result {
+            Ok(()) => JobRunOutcome::Done,
+            Err(error) => JobRunOutcome::Failed(error),
+        }
+    }
 }
 
 /// The main plugin for `gigs`. This plugin is needed for all functionality.
-#[derive(Default)]
 pub struct GraphicsJobsPlugin {
     settings: JobExecutionSettings,
+    sub_app: InternedAppLabel,
+    cleanup_set: InternedSystemSet,
+    execute_placement: JobExecutePlacement,
+    throttle: Option<JobThrottle>,
+}
+
+impl Default for GraphicsJobsPlugin {
+    fn default() -> Self {
+        Self {
+            settings: JobExecutionSettings::default(),
+            sub_app: RenderApp.intern(),
+            cleanup_set: RenderSet::Cleanup.intern(),
+            execute_placement: JobExecutePlacement::BeforeRender,
+            throttle: None,
+        }
+    }
+}
+
+impl GraphicsJobsPlugin {
+    /// Creates the plugin with the given execution settings, which are inserted as
+    /// the [`JobExecutionSettings`] resource.
+    pub fn new(settings: JobExecutionSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    /// Sets [`JobExecutionSettings::max_jobs_per_frame`].
+    pub fn with_max_jobs_per_frame(mut self, max_jobs_per_frame: u32) -> Self {
+        self.settings.max_jobs_per_frame = max_jobs_per_frame;
+        self
+    }
+
+    /// Sets [`JobExecutionSettings::time_out_frames`].
+    pub fn with_time_out_frames(mut self, time_out_frames: u32) -> Self {
+        self.settings.time_out_frames = time_out_frames;
+        self
+    }
+
+    /// Extracts and runs jobs in a custom sub-app, rather than [`RenderApp`].
+    ///
+    /// The sub-app must be set up like [`RenderApp`]: it needs an [`ExtractSchedule`]
+    /// with access to the main world, a [`Render`] schedule configured with [`RenderSet`],
+    /// and the render resources used by jobs. Entities are synced and components extracted
+    /// with `bevy_render`'s sync and extraction plugins, which always target [`RenderApp`],
+    /// so the sub-app is also responsible for providing [`RenderEntity`] mappings.
+    ///
+    /// Note: jobs run in a single sub-app per app, which all channels and system sets
+    /// are set up in. Job types and their inputs are set up in the sub-app configured
+    /// when they're initialized, so this plugin must be added before calling
+    /// [`init_graphics_job`](crate::ext::InitGraphicsJobExt::init_graphics_job), or
+    /// they'll target [`RenderApp`] instead.
+    pub fn with_sub_app(mut self, label: impl AppLabel) -> Self {
+        self.sub_app = label.intern();
+        self
+    }
+
+    /// Places [`JobSet::Cleanup`] in a different render system set, rather than
+    /// [`RenderSet::Cleanup`]. Within it, finer ordering is possible with [`JobCleanupSet`].
+    pub fn with_cleanup_set(mut self, set: impl SystemSet) -> Self {
+        self.cleanup_set = set.intern();
+        self
+    }
+
+    /// Places [`JobSet::Execute`] relative to the rest of the render schedule.
+    /// Defaults to [`JobExecutePlacement::BeforeRender`].
+    pub fn with_execute_placement(mut self, placement: JobExecutePlacement) -> Self {
+        self.execute_placement = placement;
+        self
+    }
+
+    /// Adapts [`JobExecutionSettings::max_jobs_per_frame`] to the measured frame time,
+    /// as described by [`JobThrottle`].
+    pub fn with_throttle(mut self, throttle: JobThrottle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+}
+
+/// Where [`JobSet::Execute`] is placed in the render schedule.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum JobExecutePlacement {
+    /// Jobs are executed before [`RenderSet::Render`], so their results may be used
+    /// by the render graph in the same frame.
+    #[default]
+    BeforeRender,
+    /// Jobs are executed in [`RenderSet::Cleanup`], after the render graph has run,
+    /// for jobs that should run post-render. [`JobSet::Cleanup`] must also run in
+    /// or after [`RenderSet::Cleanup`], as it does by default.
+    InCleanup,
+}
+
+/// The sub-app that graphics jobs are extracted to and run in. Defaults to [`RenderApp`].
+#[derive(Resource, Copy, Clone, Debug)]
+pub struct JobSubApp(pub InternedAppLabel);
+
+impl Default for JobSubApp {
+    fn default() -> Self {
+        Self(RenderApp.intern())
+    }
+}
+
+/// Returns the sub-app that graphics jobs run in, as configured by [`JobSubApp`].
+pub(crate) fn job_sub_app_mut(app: &mut App) -> Option<&mut SubApp> {
+    let label = app
+        .world()
+        .get_resource::<JobSubApp>()
+        .copied()
+        .unwrap_or_default();
+    app.get_sub_app_mut(label.0)
 }
 
 impl Plugin for GraphicsJobsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(self.settings);
+        app.init_resource::<JobTypePriorityOverrides>()
+            .init_resource::<DisabledJobTypes>()
+            .insert_resource(self.settings)
+            .insert_resource(JobSubApp(self.sub_app))
+            .add_systems(Update, throttle_jobs.run_if(resource_exists::<JobThrottle>));
+
+        if let Some(throttle) = self.throttle {
+            app.insert_resource(throttle);
+        }
+
+        // the sync hooks need `SyncWorldPlugin`'s resources, which only exist alongside the
+        // render sub-app. Without it, despawning a failed job would panic.
+        if job_sub_app_mut(app).is_some() {
+            app.add_plugins(SyncComponentPlugin::<JobMarker>::default());
+        }
 
         app.add_plugins((
-            SyncComponentPlugin::<JobMarker>::default(),
             ExtractResourcePlugin::<JobExecutionSettings>::default(),
+            ExtractResourcePlugin::<JobTypePriorityOverrides>::default(),
+            ExtractResourcePlugin::<DisabledJobTypes>::default(),
+            JobLatencyPlugin,
         ));
 
-        let (main_sender, main_receiver) = crossbeam_channel::unbounded();
+        let (main_sender, main_receiver) = match self.settings.result_channel_capacity {
+            Some(capacity) => crossbeam_channel::bounded(capacity),
+            None => crossbeam_channel::unbounded(),
+        };
+        let (readback_sender, readback_receiver) = crossbeam_channel::unbounded();
+        let (status_sender, status_receiver) = crossbeam_channel::unbounded();
+        let (stats_sender, stats_receiver) = crossbeam_channel::unbounded();
 
         app.insert_resource(JobResultMainWorldReceiver(main_receiver))
-            .add_systems(Update, sync_completed_jobs_main_world);
+            .insert_resource(ReadbackMainWorldReceiver(readback_receiver))
+            .insert_resource(JobStatusMainWorldReceiver(status_receiver))
+            .insert_resource(JobQueueStatsMainWorldReceiver(stats_receiver))
+            .init_resource::<JobWaiters>()
+            .init_resource::<JobsAwaitingReadback>()
+            .init_resource::<JobSinks>()
+            .init_resource::<JobErrorHandlers>()
+            .init_resource::<JobCompleteTriggers>()
+            .init_resource::<JobQueueStats>()
+            .add_event::<JobsCompleted>()
+            .add_systems(
+                Update,
+                (
+                    (advance_stalled_job_frames, sync_job_input_status_main_world).chain(),
+                    sync_job_queue_stats_main_world,
+                    // jobs with readbacks are only despawned once their readbacks are delivered
+                    (
+                        sync_completed_jobs_main_world,
+                        sync_job_readbacks_main_world,
+                    )
+                        .chain(),
+                ),
+            );
+
+        #[cfg(debug_assertions)]
+        app.add_systems(Update, runner::warn_uninitialized_jobs);
 
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
             let (sender, receiver) = crossbeam_channel::unbounded();
             render_app
                 .insert_resource(JobResultSender(sender))
                 .insert_resource(JobResultReceiver(receiver))
-                .insert_resource(JobResultMainWorldSender(main_sender));
+                .insert_resource(JobResultMainWorldSender(main_sender))
+                .insert_resource(ReadbackMainWorldSender(readback_sender))
+                .insert_resource(JobStatusMainWorldSender(status_sender))
+                .insert_resource(JobQueueStatsMainWorldSender(stats_sender))
+                .init_resource::<JobsExecutedThisFrame>()
+                .init_resource::<JobReadbacks>()
+                .init_resource::<AppFocus>()
+                .init_resource::<EntryPointValidation>()
+                .init_resource::<RegisteredJobs>()
+                .init_resource::<NextJobSequence>()
+                .init_resource::<CompletedJobs>()
+                .init_resource::<ResumedGraphJobs>()
+                .init_resource::<JobSubmitHooks>();
 
-            render_app.add_systems(ExtractSchedule, extract_job_meta);
+            render_app.add_systems(
+                ExtractSchedule,
+                (
+                    extract_job_meta,
+                    extract_disabled_jobs,
+                    extract_cancelled_jobs,
+                    extract_app_focus,
+                    extract_flush_all_jobs,
+                    extract_cancel_all_jobs,
+                    validate_entry_points,
+                ),
+            );
+
+            #[cfg(feature = "validate-layouts")]
+            render_app
+                .init_resource::<validation::layouts::LayoutValidation>()
+                .add_systems(
+                    ExtractSchedule,
+                    validation::layouts::validate_bind_group_layouts,
+                );
 
             render_app.configure_sets(
                 Render,
@@ -125,36 +473,222 @@ impl Plugin for GraphicsJobsPlugin {
                 Render,
                 (
                     JobSet::Check.after(RenderSet::Prepare),
-                    JobSet::Execute.before(RenderSet::Render),
-                    JobSet::Cleanup.in_set(RenderSet::Cleanup),
+                    JobSet::Cleanup.in_set(self.cleanup_set),
                 ),
             );
 
+            match self.execute_placement {
+                JobExecutePlacement::BeforeRender => {
+                    render_app.configure_sets(Render, JobSet::Execute.before(RenderSet::Render));
+                }
+                JobExecutePlacement::InCleanup => {
+                    render_app.configure_sets(Render, JobSet::Execute.in_set(RenderSet::Cleanup));
+                }
+            }
+
+            render_app.configure_sets(
+                Render,
+                (
+                    JobCleanupSet::CountFrames,
+                    JobCleanupSet::SyncResults,
+                    JobCleanupSet::Readbacks,
+                )
+                    .chain()
+                    .in_set(JobSet::Cleanup),
+            );
+
             render_app.add_systems(
                 Render,
                 (
-                    setup_time_out_frames.in_set(JobSet::Setup),
+                    propagate_job_priorities.in_set(JobSet::Setup),
+                    cancel_all_jobs
+                        .in_set(JobSet::Check)
+                        .before(check_job_inputs),
+                    cancel_tokened_jobs
+                        .in_set(JobSet::Check)
+                        .before(check_job_inputs),
+                    cancel_jobs.in_set(JobSet::Check),
+                    cancel_disabled_job_types
+                        .in_set(JobSet::Check)
+                        .before(check_job_inputs),
+                    limit_in_flight_jobs
+                        .in_set(JobSet::Check)
+                        .before(check_job_inputs)
+                        .after(cancel_all_jobs),
+                    process_critical_job_pipelines
+                        .in_set(JobSet::Check)
+                        .before(check_job_inputs),
+                    check_job_dependencies
+                        .in_set(JobSet::Check)
+                        .before(check_job_inputs),
                     check_job_inputs.in_set(JobSet::Check),
                     time_out_jobs.in_set(JobSet::Check),
                     run_jobs.in_set(JobSet::Execute),
-                    increment_time_out_frames.in_set(JobSet::Cleanup),
-                    sync_completed_jobs.in_set(JobSet::Cleanup),
+                    map_job_readbacks.in_set(JobSet::Execute).after(run_jobs),
+                    resume_graph_jobs
+                        .in_set(JobCleanupSet::CountFrames)
+                        .before(increment_time_out_frames),
+                    increment_time_out_frames.in_set(JobCleanupSet::CountFrames),
+                    sync_completed_jobs.in_set(JobCleanupSet::SyncResults),
+                    poll_job_readbacks.in_set(JobCleanupSet::Readbacks),
+                    // runs after completed jobs are despawned
+                    update_job_queue_stats.in_set(JobCleanupSet::Readbacks),
                 ),
             );
+        } else {
+            // kept in the main world so that jobs can be failed immediately, see `finish`
+            app.insert_resource(JobResultMainWorldSender(main_sender));
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if app.world().contains_resource::<JobResultMainWorldSender>() {
+            warn!(
+                "the `{:?}` sub-app that graphics jobs run in doesn't exist, so every job will \
+                fail with `JobError::NoRenderDevice`. Is `RenderPlugin` missing?",
+                self.sub_app
+            );
+            app.add_systems(
+                Update,
+                fail_jobs_without_sub_app.before(sync_completed_jobs_main_world),
+            );
+        }
+
+        // jobs spawned before this plugin was added won't have been marked for syncing
+        // to the render world, so we do it here.
+        let unsynced_jobs = app
+            .world_mut()
+            .query_filtered::<Entity, (With<JobMarker>, Without<SyncToRenderWorld>)>()
+            .iter(app.world())
+            .collect::<Vec<_>>();
+
+        for entity in unsynced_jobs {
+            app.world_mut().entity_mut(entity).insert(SyncToRenderWorld);
+        }
+
+        let device_info = app
+            .world()
+            .get_resource::<RenderDevice>()
+            .zip(app.world().get_resource::<RenderAdapterInfo>())
+            .map(|(render_device, adapter_info)| JobDeviceInfo {
+                adapter_info: (***adapter_info).clone(),
+                limits: render_device.limits(),
+                features: render_device.features(),
+            });
+
+        if let Some(device_info) = device_info {
+            app.insert_resource(device_info.clone());
+            if let Some(render_app) = job_sub_app_mut(app) {
+                render_app.insert_resource(device_info);
+            }
         }
     }
 }
 
+/// Information about the GPU that jobs run on, inserted into both the main world and
+/// the render world once the app has finished building. This may be used to tune jobs
+/// to the device, for example by choosing a workgroup size in a pipeline's `FromWorld`
+/// implementation, or when picking a pipeline key for a job.
+#[derive(Resource, Clone, Debug)]
+pub struct JobDeviceInfo {
+    /// The adapter's name, vendor, backend, etc.
+    pub adapter_info: AdapterInfo,
+    /// The limits of the render device
+    pub limits: Limits,
+    /// The features enabled on the render device
+    pub features: Features,
+}
+
+impl JobDeviceInfo {
+    /// Clamps a preferred one-dimensional workgroup size to the device's limits.
+    pub fn clamp_workgroup_size(&self, preferred: u32) -> u32 {
+        preferred
+            .min(self.limits.max_compute_workgroup_size_x)
+            .min(self.limits.max_compute_invocations_per_workgroup)
+            .max(1)
+    }
+}
+
 /// Settings for how jobs are scheduled each frame
 #[derive(Copy, Clone, Resource, ExtractResource)]
 pub struct JobExecutionSettings {
     /// The maximum number of jobs to execute each frame. This number
     /// may be exceeded in the case that a large number of jobs are
-    /// queued with [`Priority::Critical`](meta::Priority::Critical).
+    /// queued with [`Priority::Critical`](meta::Priority::Critical). To adapt it to
+    /// the frame time automatically, see [`JobThrottle`].
     pub max_jobs_per_frame: u32,
     /// The maximum number of frames a job should wait to execute
     /// before timing out.
     pub time_out_frames: u32,
+    /// Whether to pause non-critical jobs while no window of the app is
+    /// focused. Paused jobs don't count frames towards timing out.
+    pub pause_when_unfocused: bool,
+    /// Whether to check that the entry points of job pipelines exist in their
+    /// shaders when the pipelines are specialized. Jobs with missing entry
+    /// points will fail with [`JobError::InputsFailed`], and an error naming
+    /// the pipeline type will be logged. Intended for development builds.
+    pub validate_entry_points: bool,
+    /// The maximum CPU time to spend encoding jobs each frame, in milliseconds, if any.
+    /// Once exceeded, no more jobs are started that frame. As with `max_jobs_per_frame`,
+    /// jobs with [`Priority::Critical`](meta::Priority::Critical) ignore this budget.
+    pub max_encode_millis_per_frame: Option<f32>,
+    /// The maximum total [`JobMemoryCost`](meta::JobMemoryCost) of jobs to start each
+    /// frame, in bytes, if any. This applies alongside `max_jobs_per_frame`: a job is
+    /// only started while both limits have room for it. A single job costing more than
+    /// this still runs, if it's the first job with a cost that frame. As with
+    /// `max_jobs_per_frame`, jobs with [`Priority::Critical`](meta::Priority::Critical)
+    /// ignore this limit, but their cost still counts towards it.
+    pub max_bytes_per_frame: Option<u64>,
+    /// The maximum number of jobs that may be scheduled at once, if any. Jobs past
+    /// this limit are handled according to `eviction_policy`.
+    pub max_in_flight_jobs: Option<u32>,
+    /// What to do with jobs past `max_in_flight_jobs`.
+    pub eviction_policy: EvictionPolicy,
+    /// What to do when an internal channel between the main and render worlds is
+    /// disconnected, for example during app shutdown. Defaults to
+    /// [`ChannelFailurePolicy::Panic`] in debug builds, and [`ChannelFailurePolicy::Log`]
+    /// otherwise.
+    pub channel_failure_policy: ChannelFailurePolicy,
+    /// Whether to wrap each job's commands in a debug group named after the job's
+    /// label, so that jobs are grouped in GPU captures from tools like RenderDoc.
+    /// Defaults to `false`.
+    pub debug_markers: bool,
+    /// Whether to submit the commands of critical jobs separately from, and before,
+    /// those of other jobs executed the same frame, so that background work is queued
+    /// behind the work the frame depends on. Command buffers appended by
+    /// [`JobSubmitHooks`] are submitted with the background jobs; see
+    /// [`JobSubmitHooks::add_pre_submit`] for where hooks may insert them. Defaults to
+    /// `false`.
+    ///
+    /// Note: `wgpu` exposes a single queue per device, so both submissions go to the
+    /// same queue, and still execute in order before the render graph's commands.
+    /// Background jobs can't run concurrently with rendering; to spread their cost
+    /// over several frames, lower `max_jobs_per_frame` or use
+    /// [`JobTypeSettings::max_jobs_per_frame`].
+    pub split_submissions: bool,
+    /// Whether to catch panics in [`GraphicsJob::run`], failing the job with
+    /// [`JobError::ExecutionFailed`] and the panic message rather than unwinding
+    /// through the render schedule. Intended for development, so that one buggy job
+    /// doesn't take down the app. Has no effect if panics abort. Defaults to `false`.
+    ///
+    /// Note: a command encoder isn't unwind safe, so the commands of a panicking job
+    /// are discarded rather than submitted.
+    pub catch_panics: bool,
+    /// The number of job results and lifecycle messages that may wait to be received
+    /// by the main world, or `None` for no limit. Once full, completed jobs stay in
+    /// the render world, and their results are sent in order as room frees up, with
+    /// a warning; [`JobStarted`] events that don't fit are dropped. This bounds the
+    /// memory used if the main world stops receiving results, for example due to a
+    /// system ordering bug. Only read when [`GraphicsJobsPlugin`] is built.
+    /// Defaults to `Some(1024)`.
+    pub result_channel_capacity: Option<usize>,
+    /// How much the priority weight of a waiting job is raised for each frame it has
+    /// waited, when ordering jobs for execution, so that low-priority jobs eventually
+    /// run under sustained load from higher-priority ones. The stored [`JobPriority`](meta::JobPriority)
+    /// isn't changed, and critical jobs are unaffected. Jobs still time out as usual,
+    /// so this should be high enough for jobs to age past newer ones within
+    /// `time_out_frames`. Defaults to `0`, which disables aging.
+    pub priority_aging_rate: u32,
 }
 
 impl Default for JobExecutionSettings {
@@ -162,18 +696,257 @@ impl Default for JobExecutionSettings {
         Self {
             max_jobs_per_frame: 16,
             time_out_frames: 16,
+            pause_when_unfocused: false,
+            validate_entry_points: false,
+            max_encode_millis_per_frame: None,
+            max_bytes_per_frame: None,
+            max_in_flight_jobs: None,
+            eviction_policy: EvictionPolicy::DeferNew,
+            channel_failure_policy: ChannelFailurePolicy::default(),
+            debug_markers: false,
+            split_submissions: false,
+            catch_panics: false,
+            result_channel_capacity: Some(1024),
+            priority_aging_rate: 0,
         }
     }
 }
 
+/// Describes how jobs are handled once [`JobExecutionSettings::max_in_flight_jobs`]
+/// is exceeded.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum EvictionPolicy {
+    /// The newest jobs are deferred until there is room for them. Deferred jobs
+    /// aren't checked or executed, and don't count frames towards timing out.
+    #[default]
+    DeferNew,
+    /// The lowest-priority jobs (the newest first, among jobs of equal priority)
+    /// are cancelled with [`JobError::Cancelled`]. Priorities are ranked after any
+    /// [`JobTypePriorityOverrides`], as when executing jobs.
+    EvictLowest,
+}
+
+/// Describes how `gigs` handles a failure to send on one of its internal channels.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ChannelFailurePolicy {
+    /// Panic on failure. This is useful for catching bugs during development.
+    Panic,
+    /// Log an error and continue. The affected job results will be lost.
+    Log,
+}
+
+impl Default for ChannelFailurePolicy {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Panic
+        } else {
+            Self::Log
+        }
+    }
+}
+
+impl ChannelFailurePolicy {
+    pub(crate) fn handle<T>(self, result: Result<(), SendError<T>>) {
+        if result.is_ok() {
+            return;
+        }
+
+        match self {
+            ChannelFailurePolicy::Panic => panic!("gigs: internal channel was disconnected"),
+            ChannelFailurePolicy::Log => error!("gigs: internal channel was disconnected"),
+        }
+    }
+
+    /// Handles the result of sending on a channel that may be bounded, returning the
+    /// message back if the channel is full.
+    pub(crate) fn handle_try_send<T>(self, result: Result<(), TrySendError<T>>) -> Option<T> {
+        match result {
+            Ok(()) => None,
+            Err(TrySendError::Full(message)) => Some(message),
+            Err(TrySendError::Disconnected(message)) => {
+                self.handle(Err(SendError(message)));
+                None
+            }
+        }
+    }
+}
+
+/// A main-world resource counting the jobs currently scheduled in the render world,
+/// updated once per frame. Useful for debugging, or for throttling the spawning of jobs.
+#[derive(Copy, Clone, Resource, Default, PartialEq, Eq, Debug)]
+pub struct JobQueueStats {
+    /// The number of jobs that are scheduled, but haven't completed
+    pub pending: u32,
+    /// The number of pending jobs whose inputs are ready, but were deferred to
+    /// a later frame, for example by [`JobExecutionSettings::max_jobs_per_frame`]
+    pub ready: u32,
+    /// The number of pending jobs that are still waiting, on their inputs or otherwise
+    pub stalled: u32,
+    /// The number of jobs executed during the last frame
+    pub executed_last_frame: u32,
+}
+
+/// A one-shot resource that, when inserted into the main world, causes every ready job
+/// to be executed during the next frame, as if they all had [`Priority::Critical`](meta::Priority::Critical).
+/// This ignores [`JobExecutionSettings::max_jobs_per_frame`] and any per-type limits,
+/// and may cause stutter. It's intended for deliberate catch-up points, like the end
+/// of a loading screen. The resource is removed automatically once it has been applied.
+#[derive(Copy, Clone, Resource, Default, Debug)]
+pub struct FlushAllJobs;
+
+/// A one-shot resource that, when inserted into the main world, cancels every job
+/// that is currently waiting to execute, completing each with [`JobError::Cancelled`].
+/// Jobs spawned in the same frame are cancelled too. This is useful for recovering
+/// from a bad state, or when reloading a scene. The resource is removed automatically
+/// once it has been applied.
+#[derive(Copy, Clone, Resource, Default, Debug)]
+pub struct CancelAllJobs;
+
+/// A description of a job whose commands are about to be submitted.
+///
+/// Since `wgpu` command buffers are opaque, this is the only information
+/// available about their contents.
+#[derive(Copy, Clone, Debug)]
+pub struct SubmittedJob {
+    /// The render-world job entity
+    pub entity: Entity,
+    /// The main-world job entity, if any
+    pub main_entity: Option<Entity>,
+    /// The job's label. Its command encoder is labelled with
+    /// [`GraphicsJob::encoder_label`] instead.
+    pub label: ShortName<'static>,
+    /// Whether the job was executed as critical, either because of its priority,
+    /// or because of [`FlushAllJobs`]
+    pub critical: bool,
+}
+
+/// A render-world resource holding callbacks to be run around the submission
+/// of graphics jobs' command buffers. This allows other render plugins to
+/// coordinate their own GPU work with `gigs`.
+#[derive(Resource, Default)]
+pub struct JobSubmitHooks {
+    pre_submit: Vec<Box<dyn Fn(&World, &[SubmittedJob], &mut Vec<CommandBuffer>) + Send + Sync>>,
+    post_submit: Vec<Box<dyn Fn(&World) + Send + Sync>>,
+}
+
+impl JobSubmitHooks {
+    /// Adds a callback to be run right before jobs are submitted, with the
+    /// finished command buffers for all jobs executed this frame, and a
+    /// description of each job in the same order. Callbacks may inspect the
+    /// command buffers, or insert their own to be submitted alongside them.
+    ///
+    /// With [`JobExecutionSettings::split_submissions`], the buffers of critical jobs
+    /// come first, and that many buffers from the start are submitted separately,
+    /// before the rest. Callbacks must not insert or reorder buffers within that
+    /// prefix, whose length is the number of jobs with [`SubmittedJob::critical`] set.
+    pub fn add_pre_submit(
+        &mut self,
+        hook: impl Fn(&World, &[SubmittedJob], &mut Vec<CommandBuffer>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.pre_submit.push(Box::new(hook));
+        self
+    }
+
+    /// Adds a callback to be run right after jobs are submitted.
+    pub fn add_post_submit(&mut self, hook: impl Fn(&World) + Send + Sync + 'static) -> &mut Self {
+        self.post_submit.push(Box::new(hook));
+        self
+    }
+
+    pub(crate) fn pre_submit(
+        &self,
+        world: &World,
+        jobs: &[SubmittedJob],
+        command_buffers: &mut Vec<CommandBuffer>,
+    ) {
+        self.pre_submit
+            .iter()
+            .for_each(|hook| hook(world, jobs, command_buffers));
+    }
+
+    pub(crate) fn post_submit(&self, world: &World) {
+        self.post_submit.iter().for_each(|hook| hook(world));
+    }
+}
+
+/// Settings for how jobs of a specific type are scheduled each frame. These
+/// are applied in addition to the global [`JobExecutionSettings`].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct JobTypeSettings {
+    /// The maximum number of jobs of this type to execute each frame, if any.
+    /// As with [`JobExecutionSettings::max_jobs_per_frame`], this number may
+    /// be exceeded by jobs queued with [`Priority::Critical`](meta::Priority::Critical).
+    pub max_jobs_per_frame: Option<u32>,
+    /// The maximum number of frames a job of this type should wait to execute
+    /// before timing out, if different from [`JobExecutionSettings::time_out_frames`].
+    pub time_out_frames: Option<u32>,
+}
+
+/// A main-world resource listing job types that have been disabled at runtime, for
+/// example when a plugin providing them is unloaded. Jobs of a disabled type are
+/// cancelled with [`JobError::Cancelled`], including jobs spawned while disabled.
+///
+/// Note: Bevy doesn't support removing systems, so a disabled type's extraction and
+/// input systems keep running, and its input plugins stay registered. Disabled types
+/// may be re-enabled at any time.
+#[derive(Resource, ExtractResource, Clone, Default, Debug)]
+pub struct DisabledJobTypes(HashSet<TypeId>);
+
+impl DisabledJobTypes {
+    pub fn disable<J: GraphicsJob>(&mut self) -> &mut Self {
+        self.0.insert(TypeId::of::<J>());
+        self
+    }
+
+    pub fn enable<J: GraphicsJob>(&mut self) -> &mut Self {
+        self.0.remove(&TypeId::of::<J>());
+        self
+    }
+
+    pub fn is_disabled(&self, type_id: TypeId) -> bool {
+        self.0.contains(&type_id)
+    }
+}
+
 /// A plugin that sets up logic for a specific implementation of [`GraphicsJob`].
 /// It's recommended to call [`init_graphics_job`](crate::ext::InitGraphicsJobExt::init_graphics_job)
 /// on [`App`] rather than add this plugin manually.
-pub struct SpecializedGraphicsJobPlugin<J: GraphicsJob>(PhantomData<J>);
+pub struct SpecializedGraphicsJobPlugin<J: GraphicsJob> {
+    settings: JobTypeSettings,
+    add_extract_system: fn(&mut SubApp),
+    _marker: PhantomData<J>,
+}
 
-impl<J: GraphicsJob> Default for SpecializedGraphicsJobPlugin<J> {
+impl<J: GraphicsJob + Clone> SpecializedGraphicsJobPlugin<J> {
+    /// Creates a plugin that clones jobs into the render world
+    pub fn new(settings: JobTypeSettings) -> Self {
+        Self {
+            settings,
+            add_extract_system: |render_app| {
+                render_app.add_systems(ExtractSchedule, extract_jobs::<J>);
+            },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<J: GraphicsJob> SpecializedGraphicsJobPlugin<J> {
+    /// Creates a plugin that moves jobs into the render world rather than cloning
+    /// them, removing the job component from the main-world entity once extracted.
+    pub fn moved(settings: JobTypeSettings) -> Self {
+        Self {
+            settings,
+            add_extract_system: |render_app| {
+                render_app.add_systems(ExtractSchedule, extract_moved_jobs::<J>);
+            },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<J: GraphicsJob + Clone> Default for SpecializedGraphicsJobPlugin<J> {
     fn default() -> Self {
-        Self(PhantomData)
+        Self::new(JobTypeSettings::default())
     }
 }
 
@@ -182,21 +955,265 @@ impl<J: GraphicsJob> Plugin for SpecializedGraphicsJobPlugin<J> {
         app.add_plugins(<J as GraphicsJob>::In::plugin());
 
         app.register_required_components::<J, JobMarker>();
+        app.world_mut()
+            .get_resource_or_insert_with(JobCompleteTriggers::default)
+            .register::<J>();
 
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        if let Some(render_app) = job_sub_app_mut(app) {
             render_app
-                .add_systems(ExtractSchedule, extract_jobs::<J>)
-                .add_systems(Render, erase_jobs::<J>.in_set(JobSet::Setup));
+                .world_mut()
+                .get_resource_or_insert_with(RegisteredJobs::default)
+                .register::<J>(self.settings);
+
+            (self.add_extract_system)(render_app);
+            render_app.add_systems(
+                Render,
+                (erase_jobs::<J>, requeue_standing_jobs::<J>).in_set(JobSet::Setup),
+            );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        // jobs spawned before this plugin was added won't have their required
+        // components, so we insert them here.
+        let unmarked_jobs = app
+            .world_mut()
+            .query_filtered::<Entity, (With<J>, Without<JobMarker>)>()
+            .iter(app.world())
+            .collect::<Vec<_>>();
+
+        if !unmarked_jobs.is_empty() {
+            warn!(
+                "{} jobs of type `{}` were spawned before `init_graphics_job` was called for it. \
+                They will be scheduled now, but graphics jobs should be initialized before they're spawned.",
+                unmarked_jobs.len(),
+                J::label()
+            );
+        }
+
+        for entity in unmarked_jobs {
+            app.world_mut().entity_mut(entity).insert(JobMarker);
         }
     }
 }
 
 /// An event signaling a completed (or failed) graphics job.
-#[derive(Event, Copy, Clone, Debug)]
+#[derive(Event, Clone, Debug)]
 pub struct JobComplete(pub Result<(), JobError>);
 
+/// An event signaling a completed (or failed) graphics job of type `J`, triggered
+/// right after [`JobComplete`]. Observers of this event only run for jobs of that
+/// type, so they don't need to check which kind of job completed.
+#[derive(Event, Debug)]
+pub struct JobCompleteOf<J: GraphicsJob> {
+    pub result: Result<(), JobError>,
+    _marker: PhantomData<fn() -> J>,
+}
+
+impl<J: GraphicsJob> JobCompleteOf<J> {
+    pub fn new(result: Result<(), JobError>) -> Self {
+        Self {
+            result,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<J: GraphicsJob> Clone for JobCompleteOf<J> {
+    fn clone(&self) -> Self {
+        Self::new(self.result.clone())
+    }
+}
+
+/// The output of a completed graphics job, as given to [`GraphicsJob::on_complete`].
+#[derive(Clone, Debug)]
+pub struct JobOutput {
+    /// The main-world job entity
+    pub entity: Entity,
+    pub result: Result<(), JobError>,
+    /// The bytes read back from each of the job's [`JobReadback`](readback::JobReadback)
+    /// buffers. Readbacks that failed are omitted.
+    pub readbacks: Vec<Vec<u8>>,
+}
+
+/// Typed callbacks run on completion for each initialized job type
+#[derive(Copy, Clone)]
+struct JobCompleteCallbacks {
+    trigger: fn(&mut Commands, Entity, Result<(), JobError>),
+    on_complete: fn(&mut Commands, JobOutput),
+}
+
+/// Functions triggering [`JobCompleteOf`] and running [`GraphicsJob::on_complete`] for
+/// each initialized job type
+#[derive(Resource, Default)]
+struct JobCompleteTriggers(HashMap<TypeId, JobCompleteCallbacks>);
+
+impl JobCompleteTriggers {
+    fn register<J: GraphicsJob>(&mut self) {
+        self.0.insert(
+            TypeId::of::<J>(),
+            JobCompleteCallbacks {
+                trigger: trigger_job_complete_of::<J>,
+                on_complete: run_on_complete::<J>,
+            },
+        );
+    }
+
+    fn trigger(
+        &self,
+        commands: &mut Commands,
+        job_type: TypeId,
+        entity: Entity,
+        result: Result<(), JobError>,
+    ) {
+        if let Some(callbacks) = self.0.get(&job_type) {
+            (callbacks.trigger)(commands, entity, result);
+        }
+    }
+
+    fn on_complete(&self, job_type: TypeId) -> Option<fn(&mut Commands, JobOutput)> {
+        self.0.get(&job_type).map(|callbacks| callbacks.on_complete)
+    }
+}
+
+fn run_on_complete<J: GraphicsJob>(commands: &mut Commands, output: JobOutput) {
+    commands.queue(move |world: &mut World| {
+        let job = world
+            .get_entity_mut(output.entity)
+            .ok()
+            .and_then(|mut entity| entity.take::<J>());
+        if let Some(job) = job {
+            job.on_complete(world, output);
+        }
+    });
+}
+
+fn trigger_job_complete_of<J: GraphicsJob>(
+    commands: &mut Commands,
+    entity: Entity,
+    result: Result<(), JobError>,
+) {
+    commands.trigger_targets(JobCompleteOf::<J>::new(result), entity);
+}
+
+/// An event triggered on a job entity with a [`JobOutputImage`](meta::JobOutputImage)
+/// or [`JobImageTarget`](input::JobImageTarget) once the job has completed successfully,
+/// after [`JobComplete`]. If a job has both, this carries the [`JobOutputImage`](meta::JobOutputImage).
+///
+/// Jobs are submitted before the render graph runs in the same frame, and `wgpu`
+/// synchronizes access to the image automatically, so by the time this event is
+/// triggered the image is safe to sample from anywhere in the render graph, for
+/// example by binding it to a material. Note that the image asset must still be
+/// prepared by the render world to be sampled, which is usually the case if the job
+/// used it as an input.
+#[derive(Event, Clone, Debug)]
+pub struct JobImageReady(pub Handle<Image>);
+
+/// An event triggered on a job entity right before it starts executing, in both the
+/// render world and the main world. In the main world, this is always triggered before
+/// [`JobComplete`]. Jobs that run over several frames with a
+/// [`JobTimeSlice`](input::JobTimeSlice) only trigger this once.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct JobStarted {
+    /// The label of the job's type
+    pub label: ShortName<'static>,
+}
+
+/// An event triggered on a job entity whenever the combined status of its inputs
+/// changes, including when it is first checked. This may be used to report progress
+/// while a job waits, for example on a loading screen.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct JobInputReadyChanged(pub JobInputStatus);
+
+/// A buffered event containing the results of all jobs completed during a frame,
+/// sent once per frame if [`JobSinks::send_batched`] is enabled. Read it with an
+/// [`EventReader`](bevy_ecs::event::EventReader).
+#[derive(Event, Clone, Debug)]
+pub struct JobsCompleted(pub Vec<(Entity, Result<(), JobError>)>);
+
+/// The result of a completed graphics job, as sent to each [`JobSinks`] channel.
+#[derive(Clone, Debug)]
+pub struct JobCompletion {
+    /// The main-world job entity
+    pub entity: Entity,
+    pub result: Result<(), JobError>,
+}
+
+/// A main-world resource holding channels that the result of every completed job is
+/// forwarded to, for apps that process job results in bulk rather than with observers.
+///
+/// Channels may be added with [`register_job_sink`](crate::ext::JobSinkExt::register_job_sink),
+/// and are removed once disconnected.
+#[derive(Resource)]
+pub struct JobSinks {
+    senders: Vec<Sender<JobCompletion>>,
+    /// Whether to also trigger [`JobComplete`] on each job entity. Defaults to `true`.
+    pub trigger_observers: bool,
+    /// Whether to also send a single [`JobsCompleted`] event each frame with the
+    /// results of all jobs completed that frame. This is cheaper than observers when
+    /// many jobs complete at once, in which case `trigger_observers` may be disabled.
+    /// Defaults to `false`.
+    pub send_batched: bool,
+}
+
+impl Default for JobSinks {
+    fn default() -> Self {
+        Self {
+            senders: Vec::new(),
+            trigger_observers: true,
+            send_batched: false,
+        }
+    }
+}
+
+impl JobSinks {
+    pub fn add(&mut self, sender: Sender<JobCompletion>) {
+        self.senders.push(sender);
+    }
+
+    pub(crate) fn send(&mut self, completion: JobCompletion) {
+        self.senders
+            .retain(|sender| sender.send(completion.clone()).is_ok());
+    }
+}
+
+/// A main-world resource holding handlers for specific kinds of [`JobError`], which
+/// are run for each failed job before [`JobComplete`] is triggered. This allows
+/// errors to be handled in one place, rather than in every observer.
+#[derive(Resource, Default)]
+pub struct JobErrorHandlers {
+    handlers: HashMap<Discriminant<JobError>, Vec<JobErrorHandler>>,
+}
+
+type JobErrorHandler = Box<dyn Fn(&mut Commands, Entity, JobError) + Send + Sync>;
+
+impl JobErrorHandlers {
+    /// Adds a handler for all errors of the same variant as `error`, regardless of
+    /// any message they carry. The handler is given the main-world job entity, which
+    /// is despawned afterwards.
+    pub fn add(
+        &mut self,
+        error: JobError,
+        handler: impl Fn(&mut Commands, Entity, JobError) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.handlers
+            .entry(mem::discriminant(&error))
+            .or_default()
+            .push(Box::new(handler));
+        self
+    }
+
+    pub(crate) fn handle(&self, commands: &mut Commands, entity: Entity, error: JobError) {
+        if let Some(handlers) = self.handlers.get(&mem::discriminant(&error)) {
+            handlers
+                .iter()
+                .for_each(|handler| handler(commands, entity, error.clone()));
+        }
+    }
+}
+
 /// Describes how an incomplete job may have failed.
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub enum JobError {
     /// Signals a job that failed due to timing out, either
     /// because its needed resources were not ready in time,
@@ -206,12 +1223,49 @@ pub enum JobError {
     /// unable to be satisfied, for example if a needed
     /// extra component was not provided by the user.
     InputsFailed,
-    /// Signals a job that failed during execution.
-    ExecutionFailed,
+    /// Signals a job that failed during execution, with a message describing why.
+    /// Use [`JobError::execution_failed`] to construct this.
+    ExecutionFailed(Cow<'static, str>),
+    /// Signals a job that was cancelled before it could execute, for example
+    /// with [`CancelAllJobs`] or a [`JobCancellationToken`](meta::JobCancellationToken).
+    /// Awaited jobs whose entity is despawned before they complete are also given this.
+    Cancelled,
+    /// Signals a job that failed because one of its [`JobDependencies`](meta::JobDependencies)
+    /// failed, or because its dependencies form a cycle.
+    DependencyFailed,
+    /// Signals a job that couldn't run because the sub-app that jobs run in doesn't
+    /// exist, for example in a headless app without `RenderPlugin`.
+    NoRenderDevice,
 }
 
-fn extract_jobs<J: GraphicsJob>(
-    jobs: Extract<Query<(RenderEntity, &J), Added<JobMarker>>>,
+impl JobError {
+    /// Creates a [`JobError::ExecutionFailed`] with the given message. Static
+    /// messages don't allocate.
+    pub fn execution_failed(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::ExecutionFailed(message.into())
+    }
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut => f.write_str("job timed out"),
+            Self::InputsFailed => f.write_str("job inputs failed"),
+            Self::ExecutionFailed(message) => write!(f, "job execution failed: {message}"),
+            Self::Cancelled => f.write_str("job was cancelled"),
+            Self::DependencyFailed => f.write_str("job dependency failed"),
+            Self::NoRenderDevice => f.write_str("no render device to run job on"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+/// Clones new jobs into the render world, and standing jobs again whenever they change
+fn extract_jobs<J: GraphicsJob + Clone>(
+    jobs: Extract<
+        Query<(RenderEntity, &J), Or<(Added<JobMarker>, (With<StandingJob>, Changed<J>))>>,
+    >,
     mut commands: Commands,
 ) {
     let cloned_jobs = jobs
@@ -220,3 +1274,92 @@ fn extract_jobs<J: GraphicsJob>(
         .collect::<Vec<_>>();
     commands.insert_batch(cloned_jobs);
 }
+
+/// Moves newly synced jobs into the render world. Since the job component is removed
+/// once moved, every job still in the main world is new.
+fn extract_moved_jobs<J: GraphicsJob>(mut main_world: ResMut<MainWorld>, mut commands: Commands) {
+    let job_entities = main_world
+        .query_filtered::<(Entity, &RenderEntity), With<J>>()
+        .iter(&main_world)
+        .map(|(entity, render_entity)| (entity, render_entity.id()))
+        .collect::<Vec<_>>();
+
+    let moved_jobs = job_entities
+        .into_iter()
+        .filter_map(|(entity, render_entity)| {
+            let job = main_world.entity_mut(entity).take::<J>()?;
+            Some((render_entity, job))
+        })
+        .collect::<Vec<_>>();
+    commands.insert_batch(moved_jobs);
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use bevy_app::App;
+    use bevy_ecs::{component::Component, observer::Trigger, world::World};
+    use bevy_render::{render_resource::CommandEncoder, renderer::RenderDevice};
+
+    use crate::{
+        ext::InitGraphicsJobExt, input::JobInputItem, GraphicsJob, GraphicsJobsPlugin, JobComplete,
+        JobError,
+    };
+
+    #[derive(Component, Clone)]
+    struct TestJob;
+
+    impl GraphicsJob for TestJob {
+        type In = ();
+
+        fn run(
+            &self,
+            _world: &World,
+            _render_device: &RenderDevice,
+            _command_encoder: &mut CommandEncoder,
+            (): JobInputItem<Self, Self::In>,
+        ) -> Result<(), JobError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn duplicate_init_is_ignored() {
+        let mut app = App::new();
+        app.add_plugins(GraphicsJobsPlugin::default())
+            .init_graphics_job::<TestJob>()
+            .init_graphics_job::<TestJob>();
+        app.finish();
+        app.cleanup();
+        app.update();
+    }
+
+    #[test]
+    fn jobs_fail_without_sub_app() {
+        let mut app = App::new();
+        app.add_plugins(GraphicsJobsPlugin::default())
+            .init_graphics_job::<TestJob>();
+        app.finish();
+        app.cleanup();
+
+        let failed = Arc::new(AtomicBool::new(false));
+        let observer_failed = failed.clone();
+        app.world_mut()
+            .spawn(TestJob)
+            .observe(move |trigger: Trigger<JobComplete>| {
+                if let Err(JobError::NoRenderDevice) = trigger.event().0 {
+                    observer_failed.store(true, Ordering::Relaxed);
+                }
+            });
+
+        app.update();
+        assert!(
+            failed.load(Ordering::Relaxed),
+            "job didn't fail immediately"
+        );
+    }
+}
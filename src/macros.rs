@@ -0,0 +1,50 @@
+/// Implements [`GraphicsJob`](crate::GraphicsJob) for a type, naming each input
+/// alongside its type so that `type In` and the input pattern in `run` can't get
+/// out of sync. The job's label is left as the default.
+///
+/// ```ignore
+/// gigs::graphics_job! {
+///     impl GraphicsJob for TerrainGenJob {
+///         inputs(
+///             bind_group: JobAsBindGroup,
+///             pipeline: JobComputePipeline<TerrainGenPipeline>,
+///         );
+///
+///         fn run(&self, world, render_device, command_encoder) {
+///             let mut compute_pass = command_encoder.begin_compute_pass(&Default::default());
+///             compute_pass.set_bind_group(0, &bind_group.bind_group, &[]);
+///             compute_pass.set_pipeline(pipeline);
+///             compute_pass.dispatch_workgroups(self.size.x, self.size.y, 1);
+///             Ok(())
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! graphics_job {
+    (
+        impl GraphicsJob for $job:ty {
+            inputs($($input:ident: $input_ty:ty),* $(,)?);
+
+            fn run(
+                &$self:ident,
+                $world:ident,
+                $render_device:ident,
+                $command_encoder:ident $(,)?
+            ) $body:block
+        }
+    ) => {
+        impl $crate::GraphicsJob for $job {
+            type In = ($($input_ty,)*);
+
+            #[allow(unused_variables)]
+            fn run(
+                &$self,
+                $world: &$crate::__private::World,
+                $render_device: &$crate::__private::RenderDevice,
+                $command_encoder: &mut $crate::__private::CommandEncoder,
+                ($($input,)*): $crate::input::JobInputItem<Self, Self::In>,
+            ) -> ::core::result::Result<(), $crate::JobError> $body
+        }
+    };
+}
@@ -1,15 +1,31 @@
 use std::{
+    any::TypeId,
     cmp::Ordering,
+    marker::PhantomData,
     num::NonZero,
     ops::{Add, AddAssign},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc,
+    },
 };
 
+use bevy_app::{App, Plugin};
+use bevy_asset::Handle;
 use bevy_ecs::{
     component::Component,
-    query::Added,
-    system::{Commands, Query},
+    entity::Entity,
+    query::{Added, Changed, Has, Or, With},
+    system::{Commands, Query, Resource},
 };
-use bevy_render::{sync_world::RenderEntity, Extract};
+use bevy_image::Image;
+use bevy_render::{
+    extract_resource::ExtractResource, sync_world::RenderEntity, Extract, ExtractSchedule,
+};
+use bevy_utils::HashMap;
+use smallvec::SmallVec;
+
+use crate::{job_sub_app_mut, GraphicsJob};
 
 /// The priority level of a graphics job.
 ///
@@ -72,7 +88,7 @@ impl AddAssign for Priority {
 
 /// A generic marker for all graphics jobs.
 #[derive(Component, Default)]
-#[require(JobPriority)]
+#[require(JobPriority, JobStatus)]
 pub struct JobMarker;
 
 /// Sets the execution priority for a scheduled job.
@@ -89,10 +105,7 @@ impl JobPriority {
     pub const fn non_critical<const WEIGHT: u32>() -> Self {
         const {
             assert!(WEIGHT > 0);
-            //SAFETY: WEIGHT is not zero
-            Self(Priority::NonCritical(unsafe {
-                NonZero::new_unchecked(WEIGHT)
-            }))
+            Self(Priority::NonCritical(NonZero::new(WEIGHT).unwrap()))
         }
     }
 
@@ -102,15 +115,314 @@ impl JobPriority {
     }
 }
 
+/// A main-world resource overriding the priority of every job of a given type, including
+/// jobs that are already scheduled. This is useful for temporarily promoting a type of job
+/// to [`Priority::Critical`], for example during a cutscene, without changing each job.
+#[derive(Resource, ExtractResource, Clone, Default, Debug)]
+pub struct JobTypePriorityOverrides(HashMap<TypeId, JobPriority>);
+
+impl JobTypePriorityOverrides {
+    /// Sets the priority override for jobs of type `J`, or clears it if `None`.
+    pub fn set<J: GraphicsJob>(&mut self, priority: Option<Priority>) -> &mut Self {
+        match priority {
+            Some(priority) => self.0.insert(TypeId::of::<J>(), JobPriority(priority)),
+            None => self.0.remove(&TypeId::of::<J>()),
+        };
+        self
+    }
+
+    pub(crate) fn get(&self, type_id: TypeId) -> Option<JobPriority> {
+        self.0.get(&type_id).copied()
+    }
+}
+
+/// Overrides the number of frames a job may wait to execute before timing out, taking
+/// precedence over [`JobTypeSettings`](crate::JobTypeSettings) and
+/// [`JobExecutionSettings`](crate::JobExecutionSettings). A value of `0`
+/// ([`JobTimeout::NEVER`]) means the job never times out.
+///
+/// Note that jobs with [`Priority::Critical`] bypass the per-frame limits on executed
+/// jobs, but not this timeout: they still time out if their inputs take too long to
+/// be ready. This component must be added to a job as it is spawned.
+#[derive(Copy, Clone, Component, PartialEq, Eq, Debug)]
+pub struct JobTimeout(pub u32);
+
+impl JobTimeout {
+    /// A timeout that never expires
+    pub const NEVER: Self = Self(0);
+}
+
+/// Runs a job repeatedly, rather than once. After each successful run, the job is
+/// re-scheduled for the next frame, and its time-out is reset. The job only completes,
+/// triggering [`JobComplete`](crate::JobComplete) and being despawned, once it has run
+/// the given number of times, or if it fails or is cancelled.
+///
+/// The count is tracked in the render world, so changing this component after the
+/// job is spawned has no effect. This component must be added to a job as it is spawned.
+#[derive(Copy, Clone, Component, PartialEq, Eq, Debug)]
+pub enum JobRepeat {
+    /// Runs the job the given number of times in total
+    Times(u32),
+    /// Runs the job until it's cancelled, for example with [`CancelJob`]
+    UntilCancelled,
+}
+
+/// Keeps a job scheduled after it runs successfully, and runs it again whenever its
+/// job component changes in the main world, rather than completing it. This suits jobs
+/// that track live data, like a denoiser re-run whenever its source is edited. To
+/// re-run a job when data outside the job component changes, mark the job component
+/// as changed, for example with `set_changed`. Unlike [`JobRepeat`], which re-runs a
+/// job every frame, standing jobs only re-run on change.
+///
+/// Each run triggers [`JobStarted`](crate::JobStarted). Standing jobs only complete,
+/// triggering [`JobComplete`](crate::JobComplete) and being despawned, if they fail or
+/// are cancelled. Jobs initialized with
+/// [`init_moved_graphics_job`](crate::ext::InitGraphicsJobExt::init_moved_graphics_job)
+/// never re-run, since their job component leaves the main world. This component
+/// must be added to a job as it is spawned.
+#[derive(Copy, Clone, Component, Default, Debug)]
+pub struct StandingJob;
+
+/// Runs a job from a [`GraphicsJobNode`](crate::graph::GraphicsJobNode) in the render
+/// graph, rather than before the render graph runs. This allows jobs to run at a
+/// specific point in the graph, for example after the main opaque pass. Jobs with
+/// this component are never executed if the node isn't added to the render graph.
+/// This component must be added to a job as it is spawned.
+#[derive(Copy, Clone, Component, Default, Debug)]
+pub struct RunInGraph;
+
+/// Disables a scheduled job. Disabled jobs still have their inputs prepared,
+/// (pipelines will be compiled, bind groups prepared, etc.) but won't be executed
+/// or time out until this component is removed.
+#[derive(Copy, Clone, Component, Default, Debug)]
+pub struct JobDisabled;
+
+/// Cancels a scheduled job when added in the main world, completing it with
+/// [`JobError::Cancelled`](crate::JobError::Cancelled). [`JobComplete`](crate::JobComplete)
+/// is still triggered on the job entity. Adding this to a job that has already
+/// executed has no effect. See also [`cancel_job`](crate::ext::CancelJobExt::cancel_job).
+#[derive(Copy, Clone, Component, Default, Debug)]
+pub struct CancelJob;
+
+/// The position of a job in the current frame's execution order, inserted on
+/// render-world job entities each frame by the job runner. `None` means the job
+/// was ready, but deferred to a later frame. Intended for debugging scheduling.
+#[derive(Copy, Clone, Component, Default, PartialEq, Eq, Debug)]
+pub struct JobScheduledPosition(pub Option<u32>);
+
+/// The live status of a job, kept up to date on main-world job entities until the
+/// job completes. This may be used to drive progress bars, or to tell the user what
+/// a job is waiting for.
+///
+/// Only changes of status are synced from the render world. The `frames_stalled`
+/// counts are advanced in the main world each frame without triggering change
+/// detection, so `Changed<JobStatus>` only matches actual changes of status.
+#[derive(Copy, Clone, Component, PartialEq, Eq, Debug)]
+pub enum JobStatus {
+    /// The job is waiting on its inputs or dependencies
+    Waiting {
+        /// The number of frames the job has been waiting
+        frames_stalled: u32,
+    },
+    /// The job's inputs are ready, but it hasn't started executing, for example
+    /// because the per-frame limits in [`JobExecutionSettings`](crate::JobExecutionSettings)
+    /// deferred it
+    Ready {
+        /// The number of frames the job has been ready without executing
+        frames_stalled: u32,
+    },
+    /// The job has started executing
+    Executing,
+}
+
+impl Default for JobStatus {
+    fn default() -> Self {
+        Self::Waiting { frames_stalled: 0 }
+    }
+}
+
+/// The order in which a job was first seen by the render world, inserted on
+/// render-world job entities. Jobs of equal priority are executed in this order,
+/// oldest first, so that execution is deterministic and no job starves.
+#[derive(Copy, Clone, Component, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct JobSequence(pub u64);
+
+/// An estimate of the GPU memory a job allocates while executing, in bytes, for
+/// example for staging buffers. Jobs with a cost are only started while the total
+/// cost of jobs started that frame stays within
+/// [`JobExecutionSettings::max_bytes_per_frame`](crate::JobExecutionSettings::max_bytes_per_frame).
+/// Jobs without this component have no cost. This component must be added to a job
+/// as it is spawned.
+#[derive(Copy, Clone, Component, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct JobMemoryCost(pub u64);
+
+/// The image a job writes its output to. Once the job completes successfully, a
+/// [`JobImageReady`](crate::JobImageReady) event is triggered on the job entity.
+///
+/// This doesn't provide the image to the job; it should also be given to the job
+/// as an input, for example as a storage texture in its bind group.
+#[derive(Clone, Component, Default, PartialEq, Eq, Debug)]
+pub struct JobOutputImage(pub Handle<Image>);
+
+/// The main-world job entities that a job depends on. The job won't be checked or
+/// executed until all of its dependencies have completed successfully, and while
+/// waiting it doesn't count frames towards timing out.
+///
+/// If any dependency fails, or dependencies form a cycle, the job fails with
+/// [`JobError::DependencyFailed`](crate::JobError::DependencyFailed). Dependencies
+/// must be spawned before or alongside their dependents, or have completed
+/// successfully within the last [`time_out_frames`](crate::JobExecutionSettings::time_out_frames)
+/// frames: any other entity that isn't a scheduled job when the dependent is checked
+/// is treated as failed. This component must be added to a job as it is spawned.
+///
+/// Note: [`StandingJob`]s and jobs with [`JobRepeat::UntilCancelled`] never complete,
+/// so jobs depending on them fail rather than waiting forever.
+#[derive(Clone, Component, Default, Debug)]
+pub struct JobDependencies(pub SmallVec<[Entity; 4]>);
+
+/// A token shared between related jobs, which cancels all of them at once when
+/// [`cancel`](JobCancellationToken::cancel) is called. Cancelled jobs that haven't
+/// executed yet complete with [`JobError::Cancelled`](crate::JobError::Cancelled).
+///
+/// Clones of a token refer to the same cancellation state. This component must be
+/// added to a job as it is spawned.
+#[derive(Clone, Component, Default, Debug)]
+pub struct JobCancellationToken(Arc<AtomicBool>);
+
+impl JobCancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every job holding this token.
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::Acquire)
+    }
+}
+
 pub(super) fn extract_job_meta(
-    jobs: Extract<Query<(RenderEntity, &JobPriority), Added<JobMarker>>>,
+    jobs: Extract<
+        Query<
+            (
+                RenderEntity,
+                &JobPriority,
+                Option<&JobCancellationToken>,
+                Option<&JobDependencies>,
+                Option<&JobTimeout>,
+                Option<&JobRepeat>,
+                Option<&JobMemoryCost>,
+                Has<RunInGraph>,
+                Has<StandingJob>,
+            ),
+            Added<JobMarker>,
+        >,
+    >,
+    mut commands: Commands,
+) {
+    for (
+        render_entity,
+        priority,
+        token,
+        dependencies,
+        timeout,
+        repeat,
+        memory_cost,
+        in_graph,
+        standing,
+    ) in &jobs
+    {
+        let mut entity = commands.entity(render_entity);
+        entity.insert(*priority);
+        if in_graph {
+            entity.insert(RunInGraph);
+        }
+        if standing {
+            entity.insert(StandingJob);
+        }
+        if let Some(memory_cost) = memory_cost {
+            entity.insert(*memory_cost);
+        }
+        if let Some(timeout) = timeout {
+            entity.insert(*timeout);
+        }
+        if let Some(repeat) = repeat {
+            entity.insert(*repeat);
+        }
+        if let Some(token) = token {
+            entity.insert(token.clone());
+        }
+        if let Some(dependencies) = dependencies {
+            entity.insert(dependencies.clone());
+        }
+    }
+}
+
+pub(super) fn extract_disabled_jobs(
+    jobs: Extract<Query<(RenderEntity, Has<JobDisabled>), With<JobMarker>>>,
+    render_jobs: Query<Has<JobDisabled>>,
+    mut commands: Commands,
+) {
+    for (render_entity, disabled) in &jobs {
+        let Ok(render_disabled) = render_jobs.get(render_entity) else {
+            continue;
+        };
+        if disabled == render_disabled {
+            continue;
+        }
+
+        if disabled {
+            commands.entity(render_entity).insert(JobDisabled);
+        } else {
+            commands.entity(render_entity).remove::<JobDisabled>();
+        }
+    }
+}
+
+pub(super) fn extract_cancelled_jobs(
+    jobs: Extract<Query<RenderEntity, (With<JobMarker>, Added<CancelJob>)>>,
     mut commands: Commands,
 ) {
-    for (render_entity, priority) in &jobs {
-        commands.entity(render_entity).insert(*priority);
+    let cancelled = jobs
+        .iter()
+        .map(|render_entity| (render_entity, CancelJob))
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(cancelled);
+}
+
+/// A plugin that extracts a component on graphics job entities to the render world.
+/// It's recommended to call [`extract_job_component`](crate::ext::InitGraphicsJobExt::extract_job_component)
+/// on [`App`] rather than add this plugin manually.
+pub struct ExtractJobComponentPlugin<T>(PhantomData<T>);
+
+impl<T> Default for ExtractJobComponentPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Component + Clone> Plugin for ExtractJobComponentPlugin<T> {
+    fn build(&self, app: &mut App) {
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(ExtractSchedule, extract_job_component::<T>);
+        }
     }
 }
 
+fn extract_job_component<T: Component + Clone>(
+    jobs: Extract<Query<(RenderEntity, &T), (With<JobMarker>, Or<(Added<JobMarker>, Changed<T>)>)>>,
+    mut commands: Commands,
+) {
+    let components = jobs
+        .iter()
+        .map(|(render_entity, component)| (render_entity, component.clone()))
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(components);
+}
+
 #[cfg(test)]
 mod test {
     use std::{iter, num::NonZero};
@@ -142,7 +454,7 @@ mod test {
     #[test]
     fn priority_sum_ones() {
         const COUNT: u32 = 20;
-        let priorities = non_criticals(iter::repeat(1).take(COUNT as usize));
+        let priorities = non_criticals(iter::repeat_n(1, COUNT as usize));
         let sum = sum_priorities(priorities).unwrap();
         assert_eq!(sum, Priority::NonCritical(or_min(COUNT)));
     }
@@ -150,7 +462,7 @@ mod test {
     #[test]
     fn priority_sum_critical_left() {
         const COUNT: u32 = 20;
-        let priorities = non_criticals(iter::repeat(1).take(COUNT as usize));
+        let priorities = non_criticals(iter::repeat_n(1, COUNT as usize));
         let sum = sum_priorities(iter::once(Priority::Critical).chain(priorities)).unwrap();
         assert_eq!(sum, Priority::Critical);
     }
@@ -158,7 +470,7 @@ mod test {
     #[test]
     fn priority_sum_critical_right() {
         const COUNT: u32 = 20;
-        let priorities = non_criticals(iter::repeat(1).take(COUNT as usize));
+        let priorities = non_criticals(iter::repeat_n(1, COUNT as usize));
         let sum =
             sum_priorities(priorities.into_iter().chain(iter::once(Priority::Critical))).unwrap();
         assert_eq!(sum, Priority::Critical);
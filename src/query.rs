@@ -0,0 +1,391 @@
+//! GPU queries for graphics jobs, such as occlusion and pipeline statistics queries,
+//! and small values counted by jobs on the GPU.
+
+use core::marker::PhantomData;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::Event,
+    query::{QueryItem, With, Without},
+    schedule::IntoSystemConfigs,
+    system::{lifetimeless::Read, Commands, Query, Res},
+    world::World,
+};
+use bevy_render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_resource::{Buffer, BufferDescriptor, BufferUsages, CommandEncoder},
+    renderer::RenderDevice,
+    sync_world::MainEntity,
+    Render, RenderSet,
+};
+use bevy_utils::tracing::error;
+use wgpu::{
+    Features, PipelineStatisticsTypes, QuerySet, QuerySetDescriptor, QueryType, QUERY_SIZE,
+};
+
+use crate::{
+    input::{JobInput, JobInputStatus},
+    job_sub_app_mut,
+    readback::JobReadbacks,
+    GraphicsJob,
+};
+
+/// The type of GPU query to perform for a job.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum JobQueryType {
+    /// An occlusion query, which counts the number of samples that pass
+    /// the depth and stencil tests. The query set must be passed as the
+    /// `occlusion_query_set` of a render pass.
+    Occlusion,
+    /// A pipeline statistics query, counting the types given. This requires
+    /// the [`PIPELINE_STATISTICS_QUERY`](Features::PIPELINE_STATISTICS_QUERY)
+    /// feature, and jobs using it will fail if it isn't supported.
+    PipelineStatistics(PipelineStatisticsTypes),
+}
+
+impl JobQueryType {
+    /// The number of `u64` values produced by this query
+    fn result_count(&self) -> u32 {
+        match self {
+            JobQueryType::Occlusion => 1,
+            JobQueryType::PipelineStatistics(types) => types.bits().count_ones(),
+        }
+    }
+
+    fn is_supported(&self, render_device: &RenderDevice) -> bool {
+        match self {
+            JobQueryType::Occlusion => true,
+            JobQueryType::PipelineStatistics(_) => render_device
+                .features()
+                .contains(Features::PIPELINE_STATISTICS_QUERY),
+        }
+    }
+}
+
+/// A [`JobInput`] type that sets up a single GPU query for a job, and yields
+/// its [`QuerySet`] in `run`. The job should write to query index `0`.
+///
+/// After the job has run, the query is resolved and read back asynchronously,
+/// and a [`JobQueryResults`] event is triggered in the main world a few frames
+/// later. This component must be added to a job as it is spawned. Like
+/// [`JobReadback`](crate::readback::JobReadback), jobs that run more than once wait
+/// for the previous results to be read back first.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct JobStatisticsQuery(pub JobQueryType);
+
+/// A global event containing the read-back results of a [`JobStatisticsQuery`].
+///
+/// The results arrive some frames after the job completes, and the job entity
+/// is only despawned once they've been delivered.
+#[derive(Event, Clone, Debug)]
+pub struct JobQueryResults {
+    /// The main-world job entity
+    pub entity: Entity,
+    /// The query results. For pipeline statistics queries, these are in
+    /// the order the statistics are declared in [`PipelineStatisticsTypes`].
+    pub results: Vec<u64>,
+}
+
+#[derive(Component)]
+#[doc(hidden)]
+pub struct PreparedJobQuery {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    count: u32,
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobStatisticsQuery {
+    type Data = (
+        Read<MainEntity>,
+        Read<JobStatisticsQuery>,
+        Option<Read<PreparedJobQuery>>,
+    );
+
+    type Item<'a> = &'a QuerySet;
+
+    fn plugin() -> impl Plugin {
+        JobStatisticsQueryPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        let (main_entity, query, prepared) = data;
+        if !query.0.is_supported(world.resource::<RenderDevice>()) {
+            error!(
+                "a `{}` job uses pipeline statistics queries, which the render device doesn't support",
+                J::label()
+            );
+            return JobInputStatus::Fail;
+        }
+
+        match prepared {
+            Some(_)
+                if !world
+                    .resource::<JobReadbacks>()
+                    .in_progress_for(*main_entity) =>
+            {
+                JobInputStatus::Ready
+            }
+            _ => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.2.unwrap().query_set
+    }
+
+    fn post_run(data: QueryItem<Self::Data>, world: &World, command_encoder: &mut CommandEncoder) {
+        let (main_entity, _, Some(prepared)) = data else {
+            return;
+        };
+
+        command_encoder.resolve_query_set(&prepared.query_set, 0..1, &prepared.resolve_buffer, 0);
+        command_encoder.copy_buffer_to_buffer(
+            &prepared.resolve_buffer,
+            0,
+            &prepared.readback_buffer,
+            0,
+            prepared.count as u64 * QUERY_SIZE as u64,
+        );
+
+        world.resource::<JobReadbacks>().queue(
+            prepared.readback_buffer.clone(),
+            *main_entity,
+            deliver_query_results,
+        );
+    }
+}
+
+impl ExtractComponent for JobStatisticsQuery {
+    type QueryData = Read<JobStatisticsQuery>;
+
+    type QueryFilter = ();
+
+    type Out = JobStatisticsQuery;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(*item)
+    }
+}
+
+struct JobStatisticsQueryPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobStatisticsQueryPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobStatisticsQuery>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobStatisticsQuery>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(
+                Render,
+                prepare_job_queries.in_set(RenderSet::PrepareResources),
+            );
+        }
+    }
+}
+
+fn prepare_job_queries(
+    jobs: Query<(Entity, &JobStatisticsQuery), Without<PreparedJobQuery>>,
+    render_device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    for (entity, query) in &jobs {
+        if !query.0.is_supported(&render_device) {
+            continue;
+        }
+
+        let ty = match query.0 {
+            JobQueryType::Occlusion => QueryType::Occlusion,
+            JobQueryType::PipelineStatistics(types) => QueryType::PipelineStatistics(types),
+        };
+
+        let query_set = render_device
+            .wgpu_device()
+            .create_query_set(&QuerySetDescriptor {
+                label: Some("job_query_set"),
+                ty,
+                count: 1,
+            });
+
+        let count = query.0.result_count();
+        let size = count as u64 * QUERY_SIZE as u64;
+
+        let resolve_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("job_query_resolve_buffer"),
+            size,
+            usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("job_query_readback_buffer"),
+            size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        commands.entity(entity).insert(PreparedJobQuery {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            count,
+        });
+    }
+}
+
+fn deliver_query_results(commands: &mut Commands, entity: Entity, bytes: Vec<u8>) {
+    let results = bytes
+        .chunks_exact(QUERY_SIZE as usize)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    commands.trigger(JobQueryResults { entity, results });
+}
+
+/// A [`JobInput`] type that provides a job with a zero-initialized `u32` counter
+/// buffer, with `STORAGE` usage. This is useful for jobs that do a variable amount
+/// of work, for example GPU culling, which may atomically increment the counter.
+///
+/// After the job has run, the counter is read back asynchronously, and a [`JobCount`]
+/// event is triggered in the main world a few frames later. This component must be
+/// added to a job as it is spawned. Like [`JobReadback`](crate::readback::JobReadback),
+/// jobs that run more than once wait for the previous count to be read back first.
+/// The counter is reset to zero after each run, so each count is of a single run.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct JobCountReadback;
+
+/// A global event containing the read-back value of a [`JobCountReadback`] counter.
+///
+/// The count arrives some frames after the job completes, and the job entity
+/// is only despawned once it's been delivered.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct JobCount {
+    /// The main-world job entity
+    pub entity: Entity,
+    pub count: u32,
+}
+
+#[derive(Component)]
+#[doc(hidden)]
+pub struct PreparedJobCounter {
+    counter_buffer: Buffer,
+    readback_buffer: Buffer,
+}
+
+const COUNTER_SIZE: u64 = size_of::<u32>() as u64;
+
+impl<J: GraphicsJob> JobInput<J> for JobCountReadback {
+    type Data = (Read<MainEntity>, Option<Read<PreparedJobCounter>>);
+
+    type Item<'a> = &'a Buffer;
+
+    fn plugin() -> impl Plugin {
+        JobCountReadbackPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        match data {
+            (main_entity, Some(_))
+                if !world
+                    .resource::<JobReadbacks>()
+                    .in_progress_for(*main_entity) =>
+            {
+                JobInputStatus::Ready
+            }
+            _ => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.1.unwrap().counter_buffer
+    }
+
+    fn post_run(data: QueryItem<Self::Data>, world: &World, command_encoder: &mut CommandEncoder) {
+        let (main_entity, Some(prepared)) = data else {
+            return;
+        };
+
+        command_encoder.copy_buffer_to_buffer(
+            &prepared.counter_buffer,
+            0,
+            &prepared.readback_buffer,
+            0,
+            COUNTER_SIZE,
+        );
+        // reset the counter for the next run, if any
+        command_encoder.clear_buffer(&prepared.counter_buffer, 0, None);
+
+        world.resource::<JobReadbacks>().queue(
+            prepared.readback_buffer.clone(),
+            *main_entity,
+            deliver_count,
+        );
+    }
+}
+
+impl ExtractComponent for JobCountReadback {
+    type QueryData = Read<JobCountReadback>;
+
+    type QueryFilter = ();
+
+    type Out = JobCountReadback;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(*item)
+    }
+}
+
+struct JobCountReadbackPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobCountReadbackPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobCountReadback>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobCountReadback>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(
+                Render,
+                prepare_job_counters.in_set(RenderSet::PrepareResources),
+            );
+        }
+    }
+}
+
+fn prepare_job_counters(
+    jobs: Query<Entity, (With<JobCountReadback>, Without<PreparedJobCounter>)>,
+    render_device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    for entity in &jobs {
+        let counter_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("job_counter_buffer"),
+            size: COUNTER_SIZE,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("job_counter_readback_buffer"),
+            size: COUNTER_SIZE,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        commands.entity(entity).insert(PreparedJobCounter {
+            counter_buffer,
+            readback_buffer,
+        });
+    }
+}
+
+fn deliver_count(commands: &mut Commands, entity: Entity, bytes: Vec<u8>) {
+    let count = u32::from_le_bytes(bytes[..COUNTER_SIZE as usize].try_into().unwrap());
+    commands.trigger(JobCount { entity, count });
+}
@@ -0,0 +1,411 @@
+//! Reading back buffers written by graphics jobs to the CPU.
+
+use core::marker::PhantomData;
+use std::{mem, sync::Mutex};
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::Event,
+    query::{QueryItem, Without},
+    schedule::IntoSystemConfigs,
+    system::{lifetimeless::Read, Commands, Query, Res, ResMut, Resource},
+    world::World,
+};
+use bevy_render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_resource::{Buffer, BufferDescriptor, BufferUsages, CommandEncoder},
+    renderer::RenderDevice,
+    sync_world::MainEntity,
+    Render, RenderSet,
+};
+use bevy_utils::{tracing::error, HashMap};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use wgpu::{BufferAsyncError, Maintain, MapMode};
+
+use crate::{
+    input::{JobInput, JobInputStatus},
+    job_sub_app_mut, GraphicsJob, JobExecutionSettings, JobOutput,
+};
+
+/// A [`JobInput`] type that provides a job with a zero-initialized buffer of the given
+/// size in bytes, with `STORAGE` usage, and yields it in `run`.
+///
+/// After the job has run, the buffer is copied to a staging buffer and read back
+/// asynchronously, and a [`JobReadbackComplete`] event is triggered in the main world
+/// a few frames later. The job entity isn't despawned until then. This component must
+/// be added to a job as it is spawned.
+///
+/// The staging buffer is reused between runs, so jobs that run more than once, like
+/// [`JobRepeat`](crate::meta::JobRepeat), [`StandingJob`](crate::meta::StandingJob) or
+/// [`JobTimeSlice`](crate::input::JobTimeSlice) jobs, wait until the previous readback
+/// has been delivered before running again.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct JobReadback {
+    /// The size of the buffer in bytes. Must be a non-zero multiple of `4`, or the
+    /// job fails with [`JobError::InputsFailed`](crate::JobError::InputsFailed).
+    pub size: u64,
+}
+
+/// A global event containing the bytes read back from a job's [`JobReadback`] buffer.
+///
+/// This is triggered after [`JobComplete`](crate::JobComplete), and before the job
+/// entity is despawned. It isn't triggered if mapping the buffer fails, in which case
+/// an error is logged and the job entity is despawned without it.
+#[derive(Event, Clone, Debug)]
+pub struct JobReadbackComplete {
+    /// The main-world job entity
+    pub entity: Entity,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Component)]
+#[doc(hidden)]
+pub struct PreparedJobReadback {
+    buffer: Buffer,
+    staging_buffer: Buffer,
+    size: u64,
+}
+
+impl<J: GraphicsJob> JobInput<J> for JobReadback {
+    type Data = (
+        Read<MainEntity>,
+        Read<JobReadback>,
+        Option<Read<PreparedJobReadback>>,
+    );
+
+    type Item<'a> = &'a Buffer;
+
+    fn plugin() -> impl Plugin {
+        JobReadbackPlugin::<J>(PhantomData)
+    }
+
+    fn status(data: QueryItem<Self::Data>, world: &World) -> JobInputStatus {
+        if !is_valid_size(data.1.size) {
+            error!(
+                "the readback buffer of a `{}` job has size {}, which isn't a non-zero multiple of 4",
+                J::label(),
+                data.1.size
+            );
+            return JobInputStatus::Fail;
+        }
+
+        match data {
+            (main_entity, _, Some(_))
+                if !world
+                    .resource::<JobReadbacks>()
+                    .in_progress_for(*main_entity) =>
+            {
+                JobInputStatus::Ready
+            }
+            _ => JobInputStatus::Wait,
+        }
+    }
+
+    fn get<'a>(data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {
+        &data.2.unwrap().buffer
+    }
+
+    fn post_run(data: QueryItem<Self::Data>, world: &World, command_encoder: &mut CommandEncoder) {
+        let (main_entity, _, Some(prepared)) = data else {
+            return;
+        };
+
+        command_encoder.copy_buffer_to_buffer(
+            &prepared.buffer,
+            0,
+            &prepared.staging_buffer,
+            0,
+            prepared.size,
+        );
+
+        world.resource::<JobReadbacks>().queue(
+            prepared.staging_buffer.clone(),
+            *main_entity,
+            deliver_readback,
+        );
+    }
+}
+
+impl ExtractComponent for JobReadback {
+    type QueryData = Read<JobReadback>;
+
+    type QueryFilter = ();
+
+    type Out = JobReadback;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        Some(*item)
+    }
+}
+
+struct JobReadbackPlugin<J>(PhantomData<J>);
+
+impl<J: GraphicsJob> Plugin for JobReadbackPlugin<J> {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<ExtractComponentPlugin<JobReadback>>() {
+            return;
+        }
+
+        app.add_plugins(ExtractComponentPlugin::<JobReadback>::default());
+
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app.add_systems(
+                Render,
+                prepare_job_readbacks.in_set(RenderSet::PrepareResources),
+            );
+        }
+    }
+}
+
+fn prepare_job_readbacks(
+    jobs: Query<(Entity, &JobReadback), Without<PreparedJobReadback>>,
+    render_device: Res<RenderDevice>,
+    mut commands: Commands,
+) {
+    // jobs with invalid sizes fail when their inputs are checked
+    for (entity, readback) in jobs
+        .iter()
+        .filter(|(_, readback)| is_valid_size(readback.size))
+    {
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("job_readback_buffer"),
+            size: readback.size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let staging_buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("job_readback_staging_buffer"),
+            size: readback.size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        commands.entity(entity).insert(PreparedJobReadback {
+            buffer,
+            staging_buffer,
+            size: readback.size,
+        });
+    }
+}
+
+/// Whether a [`JobReadback`] buffer of `size` bytes can be copied and mapped
+fn is_valid_size(size: u64) -> bool {
+    size != 0 && size % 4 == 0
+}
+
+fn deliver_readback(commands: &mut Commands, entity: Entity, bytes: Vec<u8>) {
+    commands.trigger(JobReadbackComplete { entity, bytes });
+}
+
+/// A function to deliver the bytes of a completed readback to the main world,
+/// for example by triggering an event.
+pub(crate) type DeliverReadback = fn(&mut Commands, Entity, Vec<u8>);
+
+struct QueuedReadback {
+    buffer: Buffer,
+    main_entity: MainEntity,
+    deliver: DeliverReadback,
+}
+
+struct PendingReadback {
+    buffer: Buffer,
+    main_entity: MainEntity,
+    deliver: DeliverReadback,
+    mapped: Receiver<Result<(), BufferAsyncError>>,
+}
+
+/// Tracks buffers written by jobs that should be read back to the main world.
+///
+/// Readbacks are queued while jobs are encoded, mapped once their commands
+/// have been submitted, and polled each frame until the mapping completes.
+#[derive(Resource, Default)]
+pub(crate) struct JobReadbacks {
+    queued: Mutex<Vec<QueuedReadback>>,
+    pending: Vec<PendingReadback>,
+}
+
+impl JobReadbacks {
+    /// Queues a buffer to be read back once the current frame's jobs are
+    /// submitted. The buffer must have `MAP_READ` usage, and must not be
+    /// written to by any other jobs until the readback completes.
+    pub fn queue(&self, buffer: Buffer, main_entity: MainEntity, deliver: DeliverReadback) {
+        self.queued.lock().unwrap().push(QueuedReadback {
+            buffer,
+            main_entity,
+            deliver,
+        });
+    }
+
+    /// The number of readbacks in progress for a job. Queued readbacks aren't counted.
+    pub fn pending_for(&self, main_entity: MainEntity) -> u32 {
+        self.pending
+            .iter()
+            .filter(|readback| readback.main_entity == main_entity)
+            .count() as u32
+    }
+
+    /// Whether a job has readbacks queued or in progress. Jobs that run more than once,
+    /// like [`JobRepeat`](crate::meta::JobRepeat) jobs, wait on this before running
+    /// again, as their staging buffers can't be written while they're mapped.
+    pub fn in_progress_for(&self, main_entity: MainEntity) -> bool {
+        self.pending
+            .iter()
+            .any(|readback| readback.main_entity == main_entity)
+            || self
+                .queued
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|readback| readback.main_entity == main_entity)
+    }
+
+    /// Whether there are no readbacks queued or in progress.
+    pub fn is_empty(&self) -> bool {
+        self.queued.lock().unwrap().is_empty() && self.pending.is_empty()
+    }
+}
+
+pub(crate) struct ReadbackResult {
+    main_entity: MainEntity,
+    deliver: DeliverReadback,
+    /// The bytes read back, or `None` if mapping the buffer failed
+    bytes: Option<Vec<u8>>,
+}
+
+#[derive(Resource)]
+pub(crate) struct ReadbackMainWorldSender(pub Sender<ReadbackResult>);
+#[derive(Resource)]
+pub(crate) struct ReadbackMainWorldReceiver(pub Receiver<ReadbackResult>);
+
+pub(crate) fn map_job_readbacks(mut readbacks: ResMut<JobReadbacks>) {
+    let queued = mem::take(readbacks.queued.get_mut().unwrap());
+    for QueuedReadback {
+        buffer,
+        main_entity,
+        deliver,
+    } in queued
+    {
+        let (sender, mapped) = crossbeam_channel::bounded(1);
+        buffer.slice(..).map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        readbacks.pending.push(PendingReadback {
+            buffer,
+            main_entity,
+            deliver,
+            mapped,
+        });
+    }
+}
+
+pub(crate) fn poll_job_readbacks(
+    mut readbacks: ResMut<JobReadbacks>,
+    render_device: Res<RenderDevice>,
+    main_world_sender: Res<ReadbackMainWorldSender>,
+    exec_settings: Res<JobExecutionSettings>,
+) {
+    if readbacks.pending.is_empty() {
+        return;
+    }
+
+    render_device.wgpu_device().poll(Maintain::Poll);
+
+    readbacks
+        .pending
+        .retain(|readback| match readback.mapped.try_recv() {
+            Ok(Ok(())) => {
+                let bytes = readback.buffer.slice(..).get_mapped_range().to_vec();
+                readback.buffer.unmap();
+                exec_settings
+                    .channel_failure_policy
+                    .handle(main_world_sender.0.send(ReadbackResult {
+                        main_entity: readback.main_entity,
+                        deliver: readback.deliver,
+                        bytes: Some(bytes),
+                    }));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Ok(Err(_)) | Err(TryRecvError::Disconnected) => {
+                error!(
+                    "gigs: failed to map a readback buffer of job {}, so its data is lost",
+                    readback.main_entity.id()
+                );
+                // the job entity is still waiting on this readback to be despawned
+                exec_settings
+                    .channel_failure_policy
+                    .handle(main_world_sender.0.send(ReadbackResult {
+                        main_entity: readback.main_entity,
+                        deliver: readback.deliver,
+                        bytes: None,
+                    }));
+                false
+            }
+        });
+}
+
+/// Main-world job entities that have completed, but are waiting on readbacks to finish
+/// before being despawned.
+#[derive(Resource, Default)]
+pub(crate) struct JobsAwaitingReadback(HashMap<Entity, AwaitingReadback>);
+
+#[derive(Default)]
+pub(crate) struct AwaitingReadback {
+    /// The number of readbacks remaining
+    remaining: u32,
+    /// The job's [`GraphicsJob::on_complete`], with the output collected so far,
+    /// to be run once its readbacks have been delivered
+    on_complete: Option<(fn(&mut Commands, JobOutput), JobOutput)>,
+}
+
+impl JobsAwaitingReadback {
+    pub fn add(
+        &mut self,
+        entity: Entity,
+        readbacks: u32,
+        on_complete: Option<(fn(&mut Commands, JobOutput), JobOutput)>,
+    ) {
+        let awaiting = self.0.entry(entity).or_default();
+        awaiting.remaining += readbacks;
+        if on_complete.is_some() {
+            awaiting.on_complete = on_complete;
+        }
+    }
+}
+
+pub(crate) fn sync_job_readbacks_main_world(
+    readback_receiver: Res<ReadbackMainWorldReceiver>,
+    mut awaiting: ResMut<JobsAwaitingReadback>,
+    mut commands: Commands,
+) {
+    while let Ok(readback) = readback_receiver.0.try_recv() {
+        let entity = readback.main_entity.id();
+        let Some(awaiting_job) = awaiting.0.get_mut(&entity) else {
+            if let Some(bytes) = readback.bytes {
+                (readback.deliver)(&mut commands, entity, bytes);
+            }
+            continue;
+        };
+
+        if let Some(bytes) = readback.bytes {
+            if let Some((_, output)) = &mut awaiting_job.on_complete {
+                output.readbacks.push(bytes.clone());
+            }
+            (readback.deliver)(&mut commands, entity, bytes);
+        }
+
+        awaiting_job.remaining -= 1;
+        if awaiting_job.remaining == 0 {
+            let awaiting_job = awaiting.0.remove(&entity).unwrap();
+            if let Some((on_complete, output)) = awaiting_job.on_complete {
+                on_complete(&mut commands, output);
+            }
+            if let Some(mut entity) = commands.get_entity(entity) {
+                entity.despawn();
+            }
+        }
+    }
+}
@@ -1,61 +1,102 @@
-use core::iter;
+use core::{
+    any::TypeId,
+    cmp::Reverse,
+    mem,
+    panic::AssertUnwindSafe,
+    sync::atomic::{self, AtomicU32},
+};
+use std::{borrow::Cow, panic::catch_unwind, sync::Mutex};
 
 use bevy_ecs::{
+    change_detection::DetectChangesMut,
     component::Component,
-    entity::Entity,
-    query::{With, Without},
+    entity::{Entities, Entity},
+    event::EventWriter,
+    query::{Added, Changed, Has, With, Without},
     schedule::SystemSet,
-    system::{Commands, Local, Query, Res, Resource},
+    system::{Commands, Local, Query, Res, ResMut, Resource},
     world::{EntityRef, World},
 };
 use bevy_render::render_resource::CommandEncoder;
 use bevy_render::render_resource::CommandEncoderDescriptor;
+use bevy_render::render_resource::PipelineCache;
 use bevy_render::renderer::RenderDevice;
 use bevy_render::renderer::RenderQueue;
 use bevy_render::sync_world::MainEntity;
+use bevy_render::{Extract, MainWorld};
+use bevy_utils::{tracing::warn, HashMap, HashSet, Instant};
+use bevy_window::Window;
 use crossbeam_channel::Receiver;
 use crossbeam_channel::Sender;
 use disqualified::ShortName;
 
 use crate::{
-    input::{JobInput, JobInputStatus},
-    meta::JobPriority,
-    JobComplete, JobMarker,
+    input::{JobContinuation, JobImageTarget, JobInput, JobInputStatus},
+    meta::{
+        CancelJob, JobCancellationToken, JobDependencies, JobDisabled, JobMemoryCost,
+        JobOutputImage, JobPriority, JobRepeat, JobScheduledPosition, JobSequence, JobStatus,
+        JobTimeout, JobTypePriorityOverrides, Priority, RunInGraph, StandingJob,
+    },
+    readback::{JobReadbacks, JobsAwaitingReadback},
+    timing::JobTimestamps,
+    JobComplete, JobCompleteTriggers, JobImageReady, JobMarker,
 };
 
-use super::JobExecutionSettings;
-use super::{GraphicsJob, JobError};
+use super::{
+    CancelAllJobs, DisabledJobTypes, EvictionPolicy, FlushAllJobs, GraphicsJob, JobCompletion,
+    JobError, JobErrorHandlers, JobInputReadyChanged, JobOutput, JobQueueStats, JobRunOutcome,
+    JobSinks, JobStarted, JobsCompleted,
+};
+use super::{JobExecutionSettings, JobSubmitHooks, JobTypeSettings, SubmittedJob};
 
 #[derive(Copy, Clone, Component)]
 pub struct DynamicJob {
     label: ShortName<'static>,
+    type_id: TypeId,
     status: fn(EntityRef, &World) -> JobInputStatus,
-    run: fn(EntityRef, &World, &RenderDevice, &mut CommandEncoder) -> Result<(), JobError>,
+    run: fn(EntityRef, &World, &RenderDevice, &mut CommandEncoder) -> JobRunOutcome,
+    encoder_label: for<'a> fn(EntityRef<'a>) -> Cow<'a, str>,
 }
 
 impl DynamicJob {
     pub fn new<J: GraphicsJob>() -> Self {
         let label = J::label();
+        let type_id = TypeId::of::<J>();
         let status = erased_status::<J>;
         let run = erased_run::<J>;
-        Self { label, status, run }
+        let encoder_label = erased_encoder_label::<J>;
+        Self {
+            label,
+            type_id,
+            status,
+            run,
+            encoder_label,
+        }
     }
 
     pub fn label(&self) -> ShortName<'static> {
         self.label
     }
 
+    pub fn job_type(&self) -> TypeId {
+        self.type_id
+    }
+
     pub fn status(&self, entity: EntityRef, world: &World) -> JobInputStatus {
         (self.status)(entity, world)
     }
 
+    pub fn encoder_label<'a>(&self, entity: EntityRef<'a>) -> Cow<'a, str> {
+        (self.encoder_label)(entity)
+    }
+
     pub fn run(
         &self,
         entity: EntityRef,
         world: &World,
         render_device: &RenderDevice,
         command_encoder: &mut CommandEncoder,
-    ) -> Result<(), JobError> {
+    ) -> JobRunOutcome {
         (self.run)(entity, world, render_device, command_encoder)
     }
 }
@@ -65,15 +106,31 @@ fn erased_run<J: GraphicsJob>(
     world: &World,
     render_device: &RenderDevice,
     command_encoder: &mut CommandEncoder,
-) -> Result<(), JobError> {
+) -> JobRunOutcome {
     let Some((job, input_data)) = entity.get_components::<(&J, <J::In as JobInput<J>>::Data)>()
     else {
-        return Err(JobError::InputsFailed);
+        return JobRunOutcome::Failed(JobError::InputsFailed);
     };
 
     let input = <J::In as JobInput<J>>::get(input_data, world);
 
-    job.run(world, render_device, command_encoder, input)
+    let outcome = job.run_with_outcome(world, render_device, command_encoder, input);
+    if !matches!(outcome, JobRunOutcome::Done) {
+        return outcome;
+    }
+
+    if let Some(input_data) = entity.get_components::<<J::In as JobInput<J>>::Data>() {
+        <J::In as JobInput<J>>::post_run(input_data, world, command_encoder);
+    }
+
+    outcome
+}
+
+fn erased_encoder_label<J: GraphicsJob>(entity: EntityRef) -> Cow<str> {
+    match entity.get::<J>() {
+        Some(job) => job.encoder_label(),
+        None => Cow::Borrowed(J::label().original()),
+    }
 }
 
 fn erased_status<J: GraphicsJob>(entity: EntityRef, world: &World) -> JobInputStatus {
@@ -86,17 +143,92 @@ fn erased_status<J: GraphicsJob>(entity: EntityRef, world: &World) -> JobInputSt
 
 pub fn erase_jobs<J: GraphicsJob>(
     query: Query<Entity, (With<J>, Without<DynamicJob>)>,
+    mut next_sequence: ResMut<NextJobSequence>,
     mut commands: Commands,
 ) {
-    let jobs_to_erase = query.iter().collect::<Vec<_>>();
-    commands.insert_batch(
-        jobs_to_erase
-            .into_iter()
-            .zip(iter::repeat(DynamicJob::new::<J>())),
-    );
+    let jobs_to_erase = query
+        .iter()
+        .map(|entity| {
+            let sequence = JobSequence(next_sequence.0);
+            next_sequence.0 += 1;
+            (entity, (DynamicJob::new::<J>(), sequence, TimeOutFrames(0)))
+        })
+        .collect::<Vec<_>>();
+    commands.insert_batch(jobs_to_erase);
+}
+
+/// The [`JobSequence`] of the next job to be erased
+#[derive(Resource, Default)]
+pub(super) struct NextJobSequence(u64);
+
+/// The sort key for the order jobs are executed in: highest priority first,
+/// then oldest first.
+pub(super) fn execution_order(
+    priority: JobPriority,
+    sequence: Option<&JobSequence>,
+) -> (Reverse<JobPriority>, u64) {
+    (
+        Reverse(priority),
+        sequence.map_or(u64::MAX, |sequence| sequence.0),
+    )
+}
+
+/// The priority a job is ordered by after waiting `frames_waited` frames, raised by
+/// `aging_rate` for each frame so that old low-priority jobs aren't starved by newer
+/// high-priority ones. Critical jobs are unaffected.
+pub(super) fn aged_priority(
+    priority: JobPriority,
+    frames_waited: u32,
+    aging_rate: u32,
+) -> JobPriority {
+    match priority.0 {
+        Priority::NonCritical(weight) => JobPriority(Priority::NonCritical(
+            weight.saturating_add(frames_waited.saturating_mul(aging_rate)),
+        )),
+        Priority::Critical => priority,
+    }
+}
+
+/// The sort key for the order scheduled jobs are executed in, with their priority
+/// aged by the number of frames they've waited.
+pub(super) fn scheduled_execution_order(
+    entity_ref: EntityRef,
+    priority: JobPriority,
+    aging_rate: u32,
+) -> (Reverse<JobPriority>, u64) {
+    let frames_waited = entity_ref
+        .get::<TimeOutFrames>()
+        .map_or(0, |frames| frames.0);
+    execution_order(
+        aged_priority(priority, frames_waited, aging_rate),
+        entity_ref.get::<JobSequence>(),
+    )
+}
+
+/// The settings for each type of graphics job registered in the render world
+#[derive(Resource, Default)]
+pub(super) struct RegisteredJobs(HashMap<TypeId, JobTypeSettings>);
+
+impl RegisteredJobs {
+    pub fn register<J: GraphicsJob>(&mut self, settings: JobTypeSettings) {
+        self.0.insert(TypeId::of::<J>(), settings);
+    }
+
+    pub fn settings(&self, type_id: TypeId) -> JobTypeSettings {
+        self.0.get(&type_id).copied().unwrap_or_default()
+    }
 }
 
-/// The render-world system sets for graphics jobs
+/// The render-world system sets for graphics jobs.
+///
+/// These sets always run in order: `Setup`, `Check`, `Execute`, then `Cleanup`, so
+/// custom render systems may be ordered between them, for example with
+/// `.after(JobSet::Check).before(JobSet::Execute)` to run once the jobs executed
+/// this frame are known to be ready. Relative to [`RenderSet`](bevy_render::RenderSet),
+/// `Check` runs after `RenderSet::Prepare`, `Execute` is placed according to
+/// [`JobExecutePlacement`](crate::JobExecutePlacement), and `Cleanup` runs in
+/// `RenderSet::Cleanup`, unless configured otherwise on
+/// [`GraphicsJobsPlugin`](crate::GraphicsJobsPlugin).
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, SystemSet)]
 pub enum JobSet {
     /// Various graphics jobs components are setup in this set
@@ -111,83 +243,639 @@ pub enum JobSet {
     Cleanup,
 }
 
+/// The render-world system sets within [`JobSet::Cleanup`], in order
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, SystemSet)]
+pub enum JobCleanupSet {
+    /// Waiting jobs count another frame towards timing out
+    CountFrames,
+    /// The results of completed jobs are sent to the main world, and their
+    /// render-world entities are despawned. To read render-world job entities
+    /// before they're despawned, order systems before this set.
+    SyncResults,
+    /// Finished GPU readbacks are sent to the main world
+    Readbacks,
+}
+
 #[derive(Component, Copy, Clone)]
 pub(super) struct TimeOutFrames(u32);
 
-pub(super) fn setup_time_out_frames(
-    jobs: Query<Entity, (With<JobMarker>, Without<TimeOutFrames>)>,
-    mut commands: Commands,
-) {
-    let to_insert = jobs
-        .iter()
-        .zip(iter::repeat(TimeOutFrames(0)))
-        .collect::<Vec<_>>();
-    commands.insert_batch(to_insert);
-}
-
 pub(super) fn time_out_jobs(
-    jobs: Query<(Entity, Option<&MainEntity>, &TimeOutFrames)>,
+    jobs: Query<(
+        Entity,
+        Option<&MainEntity>,
+        Option<&DynamicJob>,
+        Option<&JobTimeout>,
+        &TimeOutFrames,
+    )>,
     exec_settings: Res<JobExecutionSettings>,
+    registered_jobs: Res<RegisteredJobs>,
     completed_jobs: Res<JobResultSender>,
     mut commands: Commands,
 ) {
     jobs.iter()
-        .filter(|(_, _, frames)| (frames.0 > exec_settings.time_out_frames))
-        .for_each(|(id, main_id, _)| {
-            completed_jobs
-                .0
-                .send(JobResult {
+        .filter(|(_, _, job, timeout, frames)| {
+            let time_out_frames = timeout
+                .map(|timeout| timeout.0)
+                .or_else(|| {
+                    job.and_then(|job| registered_jobs.settings(job.job_type()).time_out_frames)
+                })
+                .unwrap_or(exec_settings.time_out_frames);
+            time_out_frames != JobTimeout::NEVER.0 && frames.0 > time_out_frames
+        })
+        .for_each(|(id, main_id, _, _, _)| {
+            exec_settings
+                .channel_failure_policy
+                .handle(completed_jobs.0.send(JobResult {
                     entity: id,
                     main_entity: main_id.copied(),
                     result: Err(JobError::TimedOut),
-                })
-                .unwrap();
+                    pending_readbacks: 0,
+                    job_type: None,
+                }));
             commands.entity(id).despawn();
         });
 }
 
-pub(super) fn increment_time_out_frames(mut jobs: Query<&mut TimeOutFrames>) {
-    jobs.iter_mut().for_each(|mut frames| frames.0 += 1);
+pub(super) fn increment_time_out_frames(
+    mut jobs: Query<
+        (&mut TimeOutFrames, &JobPriority),
+        (
+            Without<JobDisabled>,
+            Without<JobDeferred>,
+            Without<JobDependencies>,
+            Without<JobIdle>,
+        ),
+    >,
+    exec_settings: Res<JobExecutionSettings>,
+    app_focus: Res<AppFocus>,
+) {
+    let paused = app_focus.is_paused(&exec_settings);
+    jobs.iter_mut()
+        .filter(|(_, priority)| !paused || priority.is_critical())
+        .for_each(|(mut frames, _)| frames.0 += 1);
+}
+
+/// Whether any window of the app is focused, extracted from the main world.
+#[derive(Resource, Copy, Clone)]
+pub(super) struct AppFocus(bool);
+
+impl Default for AppFocus {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+impl AppFocus {
+    /// Whether non-critical jobs should be paused this frame
+    pub fn is_paused(&self, exec_settings: &JobExecutionSettings) -> bool {
+        exec_settings.pause_when_unfocused && !self.0
+    }
+}
+
+pub(super) fn extract_app_focus(windows: Extract<Query<&Window>>, mut app_focus: ResMut<AppFocus>) {
+    // apps without any windows (e.g. headless apps) are always considered focused
+    app_focus.0 = windows.is_empty() || windows.iter().any(|window| window.focused);
+}
+
+/// Moves [`FlushAllJobs`] from the main world to the render world, so it only
+/// applies for a single frame.
+pub(super) fn extract_flush_all_jobs(mut main_world: ResMut<MainWorld>, mut commands: Commands) {
+    if main_world.remove_resource::<FlushAllJobs>().is_some() {
+        commands.insert_resource(FlushAllJobs);
+    } else {
+        commands.remove_resource::<FlushAllJobs>();
+    }
+}
+
+/// Moves [`CancelAllJobs`] from the main world to the render world, so it only
+/// applies for a single frame.
+pub(super) fn extract_cancel_all_jobs(mut main_world: ResMut<MainWorld>, mut commands: Commands) {
+    if main_world.remove_resource::<CancelAllJobs>().is_some() {
+        commands.insert_resource(CancelAllJobs);
+    } else {
+        commands.remove_resource::<CancelAllJobs>();
+    }
+}
+
+pub(super) fn cancel_all_jobs(
+    jobs: Query<(Entity, Option<&MainEntity>), With<DynamicJob>>,
+    cancel: Option<Res<CancelAllJobs>>,
+    exec_settings: Res<JobExecutionSettings>,
+    job_result_sender: Res<JobResultSender>,
+    mut commands: Commands,
+) {
+    if cancel.is_none() {
+        return;
+    }
+
+    for (entity, main_entity) in &jobs {
+        exec_settings
+            .channel_failure_policy
+            .handle(job_result_sender.0.send(JobResult {
+                entity,
+                main_entity: main_entity.copied(),
+                result: Err(JobError::Cancelled),
+                pending_readbacks: 0,
+                job_type: None,
+            }));
+        commands.entity(entity).despawn();
+    }
 }
 
 #[derive(Copy, Clone, Component)]
 pub struct JobReady;
 
+/// Completes jobs with [`CancelJob`] as cancelled. They're despawned along with
+/// other completed jobs in [`JobCleanupSet::SyncResults`].
+pub(super) fn cancel_jobs(
+    jobs: Query<(Entity, Option<&MainEntity>), With<CancelJob>>,
+    exec_settings: Res<JobExecutionSettings>,
+    job_result_sender: Res<JobResultSender>,
+) {
+    for (entity, main_entity) in &jobs {
+        exec_settings
+            .channel_failure_policy
+            .handle(job_result_sender.0.send(JobResult {
+                entity,
+                main_entity: main_entity.copied(),
+                result: Err(JobError::Cancelled),
+                pending_readbacks: 0,
+                job_type: None,
+            }));
+    }
+}
+
+pub(super) fn cancel_tokened_jobs(
+    jobs: Query<(Entity, Option<&MainEntity>, &JobCancellationToken)>,
+    exec_settings: Res<JobExecutionSettings>,
+    job_result_sender: Res<JobResultSender>,
+    mut commands: Commands,
+) {
+    for (entity, main_entity, token) in &jobs {
+        if !token.is_cancelled() {
+            continue;
+        }
+
+        exec_settings
+            .channel_failure_policy
+            .handle(job_result_sender.0.send(JobResult {
+                entity,
+                main_entity: main_entity.copied(),
+                result: Err(JobError::Cancelled),
+                pending_readbacks: 0,
+                job_type: None,
+            }));
+        commands.entity(entity).despawn();
+    }
+}
+
+pub(super) fn cancel_disabled_job_types(
+    jobs: Query<(Entity, Option<&MainEntity>, &DynamicJob)>,
+    disabled: Res<DisabledJobTypes>,
+    exec_settings: Res<JobExecutionSettings>,
+    job_result_sender: Res<JobResultSender>,
+    mut commands: Commands,
+) {
+    if disabled.0.is_empty() {
+        return;
+    }
+
+    for (entity, main_entity, job) in &jobs {
+        if !disabled.is_disabled(job.job_type()) {
+            continue;
+        }
+
+        exec_settings
+            .channel_failure_policy
+            .handle(job_result_sender.0.send(JobResult {
+                entity,
+                main_entity: main_entity.copied(),
+                result: Err(JobError::Cancelled),
+                pending_readbacks: 0,
+                job_type: None,
+            }));
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Marks jobs deferred by [`EvictionPolicy::DeferNew`]
+#[derive(Copy, Clone, Component)]
+pub(super) struct JobDeferred;
+
+/// Limits the number of jobs in flight. Idle jobs, like standing jobs waiting to be
+/// re-queued or jobs whose results are waiting to be sent, aren't counted.
+pub(super) fn limit_in_flight_jobs(
+    jobs: Query<
+        (
+            Entity,
+            Option<&MainEntity>,
+            &DynamicJob,
+            &JobPriority,
+            Option<&JobSequence>,
+            Has<JobDeferred>,
+        ),
+        Without<JobIdle>,
+    >,
+    exec_settings: Res<JobExecutionSettings>,
+    priority_overrides: Res<JobTypePriorityOverrides>,
+    job_result_sender: Res<JobResultSender>,
+    mut commands: Commands,
+) {
+    let Some(max_in_flight_jobs) = exec_settings.max_in_flight_jobs else {
+        return;
+    };
+
+    let mut jobs = jobs.iter().collect::<Vec<_>>();
+    match exec_settings.eviction_policy {
+        // admit the oldest jobs first
+        EvictionPolicy::DeferNew => jobs.sort_by_key(|(_, _, _, _, sequence, _)| {
+            sequence.map_or(u64::MAX, |sequence| sequence.0)
+        }),
+        // keep the highest-priority jobs, then the oldest
+        EvictionPolicy::EvictLowest => jobs.sort_by_key(|(_, _, job, priority, sequence, _)| {
+            let priority = priority_overrides.get(job.job_type()).unwrap_or(**priority);
+            execution_order(priority, *sequence)
+        }),
+    }
+
+    for (i, (entity, main_entity, _, _, _, deferred)) in jobs.into_iter().enumerate() {
+        let admitted = i < max_in_flight_jobs as usize;
+        match exec_settings.eviction_policy {
+            EvictionPolicy::DeferNew if admitted && deferred => {
+                commands.entity(entity).remove::<JobDeferred>();
+            }
+            EvictionPolicy::DeferNew if !admitted && !deferred => {
+                commands.entity(entity).insert(JobDeferred);
+            }
+            EvictionPolicy::EvictLowest if !admitted => {
+                exec_settings
+                    .channel_failure_policy
+                    .handle(job_result_sender.0.send(JobResult {
+                        entity,
+                        main_entity: main_entity.copied(),
+                        result: Err(JobError::Cancelled),
+                        pending_readbacks: 0,
+                        job_type: None,
+                    }));
+                commands.entity(entity).despawn();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Processes queued pipelines early if any critical job is waiting on its inputs, so
+/// pipelines specialized this frame may be used by critical jobs in the same frame,
+/// rather than only after [`RenderSet::Render`](bevy_render::RenderSet::Render).
+/// Pipelines that are compiled asynchronously may still take several frames.
+///
+/// Render-world job entities aren't created any earlier for critical jobs: the
+/// render world only extracts once per frame, after the main world's update, and jobs
+/// spawned during that update are already extracted and executed in the same render
+/// frame. Past that, the latency of a newly spawned job comes from waiting on its
+/// pipelines, which this addresses.
+pub(super) fn process_critical_job_pipelines(
+    jobs: Query<(&DynamicJob, &JobPriority), (Without<JobReady>, Without<JobDisabled>)>,
+    priority_overrides: Res<JobTypePriorityOverrides>,
+    mut pipeline_cache: ResMut<PipelineCache>,
+) {
+    if jobs.iter().any(|(job, priority)| {
+        priority_overrides
+            .get(job.job_type())
+            .unwrap_or(*priority)
+            .is_critical()
+    }) {
+        pipeline_cache.process_queue();
+    }
+}
+
+/// The results of recently completed jobs, by main-world entity, along with the
+/// number of frames since they completed. Failures are kept while other jobs
+/// depend on them, and successes for at least
+/// [`time_out_frames`](JobExecutionSettings::time_out_frames), so that dependents
+/// spawned after their dependencies complete still run.
+#[derive(Resource, Default)]
+pub(super) struct CompletedJobs(HashMap<Entity, (Result<(), JobError>, u32)>);
+
+/// Resolves [`JobDependencies`] for waiting jobs. Jobs whose dependencies have all
+/// succeeded have the component removed, so their inputs may be checked.
+pub(super) fn check_job_dependencies(
+    jobs: Query<(Entity, &MainEntity, &JobDependencies)>,
+    scheduled_jobs: Query<(&MainEntity, Has<StandingJob>, Option<&JobRepeat>), With<DynamicJob>>,
+    mut completed_jobs: ResMut<CompletedJobs>,
+    exec_settings: Res<JobExecutionSettings>,
+    job_result_sender: Res<JobResultSender>,
+    mut commands: Commands,
+) {
+    for (_, frames) in completed_jobs.0.values_mut() {
+        *frames = frames.saturating_add(1);
+    }
+
+    // standing jobs and jobs repeating until cancelled never complete, so jobs
+    // depending on them would wait forever
+    let scheduled = scheduled_jobs
+        .iter()
+        .map(|(main_entity, standing, repeat)| {
+            let never_completes = standing || matches!(repeat, Some(JobRepeat::UntilCancelled));
+            (main_entity.id(), never_completes)
+        })
+        .collect::<HashMap<_, _>>();
+    let graph = jobs
+        .iter()
+        .map(|(_, main_entity, dependencies)| (main_entity.id(), dependencies))
+        .collect::<HashMap<_, _>>();
+    let cyclic = find_dependency_cycles(&graph);
+
+    let mut waiting_on = HashSet::new();
+    for (entity, main_entity, dependencies) in &jobs {
+        let failed = cyclic.contains(&main_entity.id())
+            || dependencies.0.iter().any(|dependency| {
+                completed_jobs.0.get(dependency).map_or_else(
+                    || scheduled.get(dependency).copied().unwrap_or(true),
+                    |(result, _)| result.is_err(),
+                )
+            });
+
+        if failed {
+            exec_settings
+                .channel_failure_policy
+                .handle(job_result_sender.0.send(JobResult {
+                    entity,
+                    main_entity: Some(*main_entity),
+                    result: Err(JobError::DependencyFailed),
+                    pending_readbacks: 0,
+                    job_type: None,
+                }));
+        } else if dependencies
+            .0
+            .iter()
+            .all(|dependency| completed_jobs.0.contains_key(dependency))
+        {
+            commands.entity(entity).remove::<JobDependencies>();
+        } else {
+            waiting_on.extend(dependencies.0.iter().copied());
+        }
+    }
+
+    completed_jobs.0.retain(|entity, (result, frames)| {
+        waiting_on.contains(entity) || (result.is_ok() && *frames <= exec_settings.time_out_frames)
+    });
+}
+
+/// Finds all jobs that are part of a dependency cycle, and so can never run.
+/// Jobs that depend on a cycle without being part of it will fail once the
+/// jobs in the cycle do.
+fn find_dependency_cycles(graph: &HashMap<Entity, &JobDependencies>) -> HashSet<Entity> {
+    let mut cyclic = HashSet::new();
+    for &job in graph.keys() {
+        let mut visited = HashSet::new();
+        let mut stack = vec![job];
+        'search: while let Some(current) = stack.pop() {
+            let Some(dependencies) = graph.get(&current) else {
+                continue;
+            };
+            for &dependency in &dependencies.0 {
+                if dependency == job {
+                    cyclic.insert(job);
+                    break 'search;
+                }
+                if visited.insert(dependency) {
+                    stack.push(dependency);
+                }
+            }
+        }
+    }
+    cyclic
+}
+
+/// The priority a job was spawned with, before the priorities of its dependents
+/// were propagated to it
+#[derive(Copy, Clone, Component)]
+pub(super) struct BaseJobPriority(JobPriority);
+
+pub(super) fn propagate_job_priorities(
+    mut jobs: Query<(
+        Entity,
+        &MainEntity,
+        &mut JobPriority,
+        Option<&BaseJobPriority>,
+        Option<&JobDependencies>,
+    )>,
+    mut commands: Commands,
+) {
+    let mut new_bases = Vec::new();
+    let graph = jobs
+        .iter()
+        .map(|(entity, main_entity, priority, base, dependencies)| {
+            let base = match base {
+                Some(base) => base.0,
+                None => {
+                    new_bases.push((entity, BaseJobPriority(*priority)));
+                    *priority
+                }
+            };
+            (main_entity.id(), (base, dependencies))
+        })
+        .collect::<HashMap<_, _>>();
+    let priorities = propagate_priorities(&graph);
+    drop(graph);
+    commands.insert_batch(new_bases);
+
+    for (_, main_entity, mut priority, ..) in &mut jobs {
+        if let Some(&propagated) = priorities.get(&main_entity.id()) {
+            if *priority != propagated {
+                *priority = propagated;
+            }
+        }
+    }
+}
+
+/// Adds the base priority of each job to all of its direct and indirect dependencies.
+/// Each dependency is counted once per job, even if the job depends on it through
+/// several paths.
+fn propagate_priorities(
+    graph: &HashMap<Entity, (JobPriority, Option<&JobDependencies>)>,
+) -> HashMap<Entity, JobPriority> {
+    let mut priorities = graph
+        .iter()
+        .map(|(&job, &(base, _))| (job, base))
+        .collect::<HashMap<_, _>>();
+
+    for (&job, &(base, _)) in graph {
+        let mut visited = HashSet::new();
+        let mut stack = vec![job];
+        while let Some(current) = stack.pop() {
+            let Some((_, Some(dependencies))) = graph.get(&current) else {
+                continue;
+            };
+            for &dependency in &dependencies.0 {
+                if dependency != job && visited.insert(dependency) {
+                    if let Some(priority) = priorities.get_mut(&dependency) {
+                        priority.0 += base.0;
+                    }
+                    stack.push(dependency);
+                }
+            }
+        }
+    }
+    priorities
+}
+
+/// The last input status of a job, used to detect transitions
+#[derive(Copy, Clone, Component)]
+pub(super) struct LastInputStatus(JobInputStatus);
+
 pub(super) fn check_job_inputs(
-    jobs: Query<(EntityRef, Option<&MainEntity>, &DynamicJob), Without<JobReady>>,
+    jobs: Query<
+        (
+            EntityRef,
+            Option<&MainEntity>,
+            &DynamicJob,
+            Option<&LastInputStatus>,
+        ),
+        (
+            Without<JobReady>,
+            Without<JobDisabled>,
+            Without<JobDeferred>,
+            Without<JobDependencies>,
+            Without<CancelJob>,
+            Without<JobIdle>,
+        ),
+    >,
     world: &World,
+    exec_settings: Res<JobExecutionSettings>,
     job_result_sender: Res<JobResultSender>,
+    status_sender: Res<JobStatusMainWorldSender>,
     mut commands: Commands,
 ) {
-    let to_insert = jobs
-        .iter()
-        .filter_map(
-            |(entity, main_entity, job)| match job.status(entity, world) {
-                JobInputStatus::Ready => Some(entity.id()),
-                JobInputStatus::Wait => None,
-                JobInputStatus::Fail => {
-                    job_result_sender
-                        .0
-                        .send(JobResult {
-                            entity: entity.id(),
-                            main_entity: main_entity.copied(),
-                            result: Err(JobError::InputsFailed),
-                        })
-                        .unwrap();
-                    None
+    let mut ready = Vec::new();
+    let mut changed = Vec::new();
+
+    for (entity, main_entity, job, last_status) in &jobs {
+        let status = job.status(entity, world);
+
+        if last_status.is_none_or(|last| last.0 != status) {
+            changed.push((entity.id(), LastInputStatus(status)));
+            if let Some(main_entity) = main_entity {
+                exec_settings
+                    .channel_failure_policy
+                    .handle(status_sender.0.send((*main_entity, status)));
+            }
+        }
+
+        match status {
+            JobInputStatus::Ready => ready.push((entity.id(), JobReady)),
+            JobInputStatus::Wait => {}
+            JobInputStatus::Fail => {
+                exec_settings
+                    .channel_failure_policy
+                    .handle(job_result_sender.0.send(JobResult {
+                        entity: entity.id(),
+                        main_entity: main_entity.copied(),
+                        result: Err(JobError::InputsFailed),
+                        pending_readbacks: 0,
+                        job_type: None,
+                    }));
+            }
+        }
+    }
+
+    // jobs may be cancelled and despawned concurrently
+    commands.try_insert_batch(ready);
+    commands.try_insert_batch(changed);
+}
+
+#[derive(Resource)]
+pub(super) struct JobStatusMainWorldReceiver(pub Receiver<(MainEntity, JobInputStatus)>);
+#[derive(Resource)]
+pub(super) struct JobStatusMainWorldSender(pub Sender<(MainEntity, JobInputStatus)>);
+
+pub(super) fn sync_job_input_status_main_world(
+    status_receiver: Res<JobStatusMainWorldReceiver>,
+    mut job_statuses: Query<&mut JobStatus>,
+    mut commands: Commands,
+) {
+    while let Ok((main_entity, status)) = status_receiver.0.try_recv() {
+        if let Ok(mut job_status) = job_statuses.get_mut(main_entity.id()) {
+            match (status, *job_status) {
+                // repeating and standing jobs are checked again after they execute
+                (JobInputStatus::Ready, JobStatus::Waiting { .. } | JobStatus::Executing) => {
+                    *job_status = JobStatus::Ready { frames_stalled: 0 };
                 }
-            },
-        )
-        .zip(iter::repeat(JobReady))
-        .collect::<Vec<_>>();
-    commands.insert_batch(to_insert)
+                (JobInputStatus::Wait, JobStatus::Ready { .. } | JobStatus::Executing) => {
+                    *job_status = JobStatus::Waiting { frames_stalled: 0 };
+                }
+                _ => {}
+            }
+        }
+        commands.trigger_targets(JobInputReadyChanged(status), main_entity.id());
+    }
+}
+
+/// Advances the stall counts of main-world jobs that haven't started executing
+pub(super) fn advance_stalled_job_frames(mut job_statuses: Query<&mut JobStatus>) {
+    for mut job_status in &mut job_statuses {
+        if let JobStatus::Waiting { frames_stalled } | JobStatus::Ready { frames_stalled } =
+            job_status.bypass_change_detection()
+        {
+            *frames_stalled = frames_stalled.saturating_add(1);
+        }
+    }
+}
+
+/// The number of jobs executed by [`run_jobs`] this frame
+#[derive(Resource, Default)]
+pub(super) struct JobsExecutedThisFrame(AtomicU32);
+
+#[derive(Resource)]
+pub(super) struct JobQueueStatsMainWorldSender(pub Sender<JobQueueStats>);
+#[derive(Resource)]
+pub(super) struct JobQueueStatsMainWorldReceiver(pub Receiver<JobQueueStats>);
+
+pub(super) fn update_job_queue_stats(
+    jobs: Query<Has<JobReady>, With<DynamicJob>>,
+    executed: Res<JobsExecutedThisFrame>,
+    stats_sender: Res<JobQueueStatsMainWorldSender>,
+    exec_settings: Res<JobExecutionSettings>,
+) {
+    let mut stats = JobQueueStats {
+        executed_last_frame: executed.0.swap(0, atomic::Ordering::Relaxed),
+        ..Default::default()
+    };
+    for ready in &jobs {
+        stats.pending += 1;
+        if ready {
+            stats.ready += 1;
+        } else {
+            stats.stalled += 1;
+        }
+    }
+
+    exec_settings
+        .channel_failure_policy
+        .handle(stats_sender.0.send(stats));
+}
+
+pub(super) fn sync_job_queue_stats_main_world(
+    stats_receiver: Res<JobQueueStatsMainWorldReceiver>,
+    mut stats: ResMut<JobQueueStats>,
+) {
+    if let Some(latest) = stats_receiver.0.try_iter().last() {
+        *stats = latest;
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub(super) struct JobResult {
     entity: Entity,
     main_entity: Option<MainEntity>,
     result: Result<(), JobError>,
+    /// The number of readbacks still in progress for the job, set when the result
+    /// is synced to the main world. The job entity is despawned once they finish.
+    pending_readbacks: u32,
+    /// The type of the job, set when the result is synced to the main world, for
+    /// triggering [`JobCompleteOf`](crate::JobCompleteOf)
+    job_type: Option<TypeId>,
 }
 
 #[derive(Resource)]
@@ -196,76 +884,1621 @@ pub(super) struct JobResultReceiver(pub Receiver<JobResult>);
 pub(super) struct JobResultSender(pub Sender<JobResult>);
 
 #[derive(Resource)]
-pub(super) struct JobResultMainWorldReceiver(pub Receiver<JobResult>);
+pub(super) struct JobResultMainWorldReceiver(pub Receiver<JobMainWorldMessage>);
 #[derive(Resource)]
-pub(super) struct JobResultMainWorldSender(pub Sender<JobResult>);
+pub(super) struct JobResultMainWorldSender(pub Sender<JobMainWorldMessage>);
 
+/// A job lifecycle event sent to the main world. Both kinds of messages are sent
+/// through the same channel so that they're received in order.
+#[derive(Clone)]
+pub(super) enum JobMainWorldMessage {
+    Started {
+        main_entity: MainEntity,
+        label: ShortName<'static>,
+    },
+    Completed(JobResult),
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(super) fn sync_completed_jobs(
     job_result_receiver: Res<JobResultReceiver>,
     main_job_result_sender: Res<JobResultMainWorldSender>,
+    exec_settings: Res<JobExecutionSettings>,
+    readbacks: Res<JobReadbacks>,
+    mut job_results: ResMut<CompletedJobs>,
+    mut repeating_jobs: Query<&mut JobRepeat>,
+    standing_jobs: Query<(), With<StandingJob>>,
+    job_types: Query<&DynamicJob>,
+    mut completed_jobs: Local<HashSet<Entity>>,
+    mut deferred_results: Local<Vec<JobResult>>,
     mut commands: Commands,
 ) {
-    while let Ok(job) = job_result_receiver.0.try_recv() {
-        main_job_result_sender.0.send(job).unwrap();
-        commands.trigger_targets(JobComplete(job.result), job.entity);
-        if let Some(mut entity) = commands.get_entity(job.entity) {
-            entity.despawn();
+    // results that didn't fit in the main world channel are retried first, in order
+    for job in mem::take(&mut *deferred_results) {
+        if let Some(job) = send_completed_job(
+            job,
+            &main_job_result_sender,
+            &exec_settings,
+            &readbacks,
+            &mut commands,
+        ) {
+            deferred_results.push(job);
         }
     }
-}
 
-pub(super) fn sync_completed_jobs_main_world(
-    job_result_receiver: Res<JobResultMainWorldReceiver>,
-    mut commands: Commands,
-) {
-    while let Ok(job) = job_result_receiver.0.try_recv() {
-        if let Some(main_entity) = job.main_entity {
-            commands.trigger_targets(JobComplete(job.result), main_entity.id());
-            if let Some(mut entity) = commands.get_entity(main_entity.id()) {
-                entity.despawn();
-            }
+    // a job may fail in more than one way in the same frame, for example by
+    // timing out as its inputs fail. Only the first result is reported.
+    completed_jobs.clear();
+    completed_jobs.extend(deferred_results.iter().map(|job| job.entity));
+    let previously_deferred = deferred_results.len();
+    while let Ok(mut job) = job_result_receiver.0.try_recv() {
+        if !completed_jobs.insert(job.entity) {
+            continue;
         }
-    }
-}
 
-pub(super) fn run_jobs(
-    jobs: Query<(EntityRef, Option<&MainEntity>, &DynamicJob, &JobPriority), With<JobReady>>,
-    world: &World,
-    render_device: Res<RenderDevice>,
-    render_queue: Res<RenderQueue>,
-    exec_settings: Res<JobExecutionSettings>,
-    job_result_sender: Res<JobResultSender>,
-    mut command_encoders: Local<Vec<CommandEncoder>>,
-) {
-    let sorted_jobs = jobs
-        .iter()
-        .sort::<&JobPriority>()
-        .rev()
-        .enumerate()
-        .take_while(|(i, (_, _, _, priority))| {
-            priority.is_critical() || (*i as u32) < exec_settings.max_jobs_per_frame
-        })
-        .map(|(_, a)| a);
+        // repeating jobs are re-scheduled after each successful run but the last
+        if let (Ok(()), Ok(mut repeat)) = (&job.result, repeating_jobs.get_mut(job.entity)) {
+            let repeats = match *repeat {
+                JobRepeat::Times(times) if times > 1 => {
+                    *repeat = JobRepeat::Times(times - 1);
+                    true
+                }
+                JobRepeat::Times(_) => false,
+                JobRepeat::UntilCancelled => true,
+            };
 
-    for (entity_ref, main_entity, job, _) in sorted_jobs {
-        let mut command_encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some(job.label().original()),
-        });
+            if repeats {
+                commands
+                    .entity(job.entity)
+                    .remove::<(JobReady, LastInputStatus, JobScheduledPosition)>()
+                    .insert(TimeOutFrames(0));
+                continue;
+            }
+        }
 
-        let result = job.run(entity_ref, world, &render_device, &mut command_encoder);
-        if result.is_ok() {
-            command_encoders.push(command_encoder);
+        // standing jobs go idle after each successful run, until their job component changes
+        if job.result.is_ok() && standing_jobs.contains(job.entity) {
+            commands
+                .entity(job.entity)
+                .remove::<(JobReady, LastInputStatus, JobScheduledPosition)>()
+                .insert((TimeOutFrames(0), JobIdle));
+            continue;
         }
 
-        job_result_sender
-            .0
-            .send(JobResult {
-                entity: entity_ref.id(),
-                main_entity: main_entity.copied(),
-                result,
-            })
-            .unwrap();
+        if let Some(main_entity) = job.main_entity {
+            job.job_type = job_types.get(job.entity).ok().map(DynamicJob::job_type);
+            job_results
+                .0
+                .insert(main_entity.id(), (job.result.clone(), 0));
+        }
+
+        if !deferred_results.is_empty() {
+            // keep results in order behind those already waiting
+            deferred_results.push(job);
+        } else if let Some(job) = send_completed_job(
+            job,
+            &main_job_result_sender,
+            &exec_settings,
+            &readbacks,
+            &mut commands,
+        ) {
+            deferred_results.push(job);
+        }
     }
 
-    render_queue.submit(command_encoders.drain(..).map(|cmd| cmd.finish()));
+    // jobs whose results are deferred stay in the render world, without being
+    // scheduled, until their results are sent. Jobs that were cancelled or timed
+    // out have already been despawned.
+    for job in &deferred_results[previously_deferred..] {
+        if let Some(mut entity) = commands.get_entity(job.entity) {
+            entity
+                .remove::<(JobReady, LastInputStatus, JobScheduledPosition)>()
+                .try_insert((TimeOutFrames(0), JobIdle));
+        }
+    }
+    if deferred_results.len() > previously_deferred {
+        warn!(
+            "gigs: the job result channel is full, so {} job results were deferred, and {} \
+            are waiting in total. Is the main world receiving them? \
+            See `JobExecutionSettings::result_channel_capacity`.",
+            deferred_results.len() - previously_deferred,
+            deferred_results.len()
+        );
+    }
+}
+
+/// Sends the result of a completed job to the main world, then triggers [`JobComplete`]
+/// on the render-world job entity and despawns it. If the channel is full, the result
+/// is returned to be sent again later.
+///
+/// The job's pending readbacks are counted as it's sent, since readbacks may finish,
+/// and be sent to the main world, while its result is deferred.
+fn send_completed_job(
+    mut job: JobResult,
+    main_job_result_sender: &JobResultMainWorldSender,
+    exec_settings: &JobExecutionSettings,
+    readbacks: &JobReadbacks,
+    commands: &mut Commands,
+) -> Option<JobResult> {
+    if let Some(main_entity) = job.main_entity {
+        job.pending_readbacks = readbacks.pending_for(main_entity);
+    }
+    let entity = job.entity;
+    let result = job.result.clone();
+    if let Some(JobMainWorldMessage::Completed(job)) =
+        exec_settings.channel_failure_policy.handle_try_send(
+            main_job_result_sender
+                .0
+                .try_send(JobMainWorldMessage::Completed(job)),
+        )
+    {
+        return Some(job);
+    }
+
+    commands.trigger_targets(JobComplete(result), entity);
+    if let Some(mut entity) = commands.get_entity(entity) {
+        entity.despawn();
+    }
+    None
+}
+
+/// Marks a job that has run and isn't scheduled: either a [`StandingJob`] waiting for
+/// its job component to change, or a job whose result is waiting for room in the
+/// main world channel
+#[derive(Component, Copy, Clone)]
+pub(super) struct JobIdle;
+
+/// Re-schedules idle standing jobs whose job component was re-extracted this frame
+pub(super) fn requeue_standing_jobs<J: GraphicsJob>(
+    jobs: Query<Entity, (With<JobIdle>, Changed<J>)>,
+    mut commands: Commands,
+) {
+    for entity in &jobs {
+        commands.entity(entity).remove::<JobIdle>();
+    }
+}
+
+/// Fails every job as it's spawned, in apps where the sub-app that jobs run in
+/// doesn't exist. Otherwise, jobs would wait forever.
+pub(super) fn fail_jobs_without_sub_app(
+    jobs: Query<Entity, Added<JobMarker>>,
+    main_world_sender: Res<JobResultMainWorldSender>,
+    exec_settings: Res<JobExecutionSettings>,
+    mut deferred: Local<Vec<Entity>>,
+) {
+    let pending = mem::take(&mut *deferred);
+    for entity in pending.into_iter().chain(&jobs) {
+        let message = JobMainWorldMessage::Completed(JobResult {
+            entity,
+            main_entity: Some(MainEntity::from(entity)),
+            result: Err(JobError::NoRenderDevice),
+            pending_readbacks: 0,
+            job_type: None,
+        });
+        if exec_settings
+            .channel_failure_policy
+            .handle_try_send(main_world_sender.0.try_send(message))
+            .is_some()
+        {
+            deferred.push(entity);
+        }
+    }
+}
+
+/// Warns about entities that look like graphics jobs, but have no [`JobMarker`], which
+/// usually means `init_graphics_job` wasn't called for their job type. Such entities
+/// would otherwise wait forever without a trace. Since job components can't be
+/// recognized without being initialized, this only checks entities with a
+/// [`JobPriority`] or other job metadata components, or that are being awaited, which
+/// covers jobs spawned with [`spawn_graphics_job`](crate::SpawnGraphicsJobExt::spawn_graphics_job).
+/// Only added in debug builds.
+#[cfg(debug_assertions)]
+pub(super) fn warn_uninitialized_jobs(
+    jobs_with_metadata: Query<
+        Entity,
+        (
+            Without<JobMarker>,
+            bevy_ecs::query::Or<(
+                Added<JobPriority>,
+                Added<JobTimeout>,
+                Added<JobDependencies>,
+                Added<JobOutputImage>,
+                Added<JobCancellationToken>,
+                Added<JobMemoryCost>,
+                Added<RunInGraph>,
+            )>,
+        ),
+    >,
+    markers: Query<Has<JobMarker>>,
+    job_waiters: Res<JobWaiters>,
+    mut warned: Local<HashSet<Entity>>,
+) {
+    let awaited_jobs = job_waiters
+        .waiters
+        .keys()
+        .copied()
+        .filter(|&entity| matches!(markers.get(entity), Ok(false)));
+
+    for entity in jobs_with_metadata.iter().chain(awaited_jobs) {
+        if warned.insert(entity) {
+            warn!(
+                "{entity} looks like a graphics job, but isn't scheduled. \
+                Was `init_graphics_job` called for its job type?"
+            );
+        }
+    }
+    warned.retain(|&entity| markers.contains(entity));
+}
+
+type JobWaiter = async_channel::Sender<Result<(), JobError>>;
+
+/// Channels waiting on the results of specific jobs in the main world, along with
+/// the results of completed jobs whose entities haven't been despawned yet, for
+/// channels added after the job completed.
+#[derive(Resource, Default)]
+pub(super) struct JobWaiters {
+    waiters: HashMap<Entity, Vec<JobWaiter>>,
+    completed: HashMap<Entity, Result<(), JobError>>,
+}
+
+impl JobWaiters {
+    /// Adds a channel to send the result of a job to once it completes. If the job has
+    /// already completed, its result is sent immediately, and if the job entity doesn't
+    /// exist, it's sent [`JobError::Cancelled`].
+    pub fn add(world: &mut World, entity: Entity, waiter: JobWaiter) {
+        let exists = world.get_entity(entity).is_ok();
+        let mut job_waiters = world.resource_mut::<JobWaiters>();
+        if let Some(result) = job_waiters.completed.get(&entity) {
+            let _ = waiter.try_send(result.clone());
+        } else if !exists {
+            let _ = waiter.try_send(Err(JobError::Cancelled));
+        } else {
+            job_waiters.waiters.entry(entity).or_default().push(waiter);
+        }
+    }
+
+    /// Sends the result of a completed job to its waiting channels
+    fn complete(&mut self, entity: Entity, result: &Result<(), JobError>) {
+        if let Some(waiters) = self.waiters.remove(&entity) {
+            for waiter in waiters {
+                let _ = waiter.try_send(result.clone());
+            }
+        }
+        self.completed.insert(entity, result.clone());
+    }
+
+    /// Removes channels whose receivers have been dropped, for example by dropping a
+    /// [`JobHandle`](crate::JobHandle), so they don't outlive jobs that never complete.
+    /// Channels waiting on jobs that were despawned without completing are sent
+    /// [`JobError::Cancelled`], and the results of despawned jobs are forgotten.
+    fn remove_closed(&mut self, entities: &Entities) {
+        self.waiters.retain(|&entity, waiters| {
+            if !entities.contains(entity) {
+                for waiter in waiters.drain(..) {
+                    let _ = waiter.try_send(Err(JobError::Cancelled));
+                }
+            }
+            waiters.retain(|waiter| !waiter.is_closed());
+            !waiters.is_empty()
+        });
+        self.completed
+            .retain(|&entity, _| entities.contains(entity));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sync_completed_jobs_main_world(
+    job_result_receiver: Res<JobResultMainWorldReceiver>,
+    mut job_waiters: ResMut<JobWaiters>,
+    mut awaiting_readbacks: ResMut<JobsAwaitingReadback>,
+    mut job_sinks: ResMut<JobSinks>,
+    error_handlers: Res<JobErrorHandlers>,
+    complete_triggers: Res<JobCompleteTriggers>,
+    output_images: Query<(Option<&JobOutputImage>, Option<&JobImageTarget>)>,
+    mut job_statuses: Query<&mut JobStatus>,
+    mut jobs_completed: EventWriter<JobsCompleted>,
+    entities: &Entities,
+    mut commands: Commands,
+) {
+    let mut batch = Vec::new();
+    while let Ok(message) = job_result_receiver.0.try_recv() {
+        let job = match message {
+            JobMainWorldMessage::Started { main_entity, label } => {
+                if let Ok(mut job_status) = job_statuses.get_mut(main_entity.id()) {
+                    *job_status = JobStatus::Executing;
+                }
+                commands.trigger_targets(JobStarted { label }, main_entity.id());
+                continue;
+            }
+            JobMainWorldMessage::Completed(job) => job,
+        };
+
+        if let Some(main_entity) = job.main_entity {
+            job_waiters.complete(main_entity.id(), &job.result);
+
+            job_sinks.send(JobCompletion {
+                entity: main_entity.id(),
+                result: job.result.clone(),
+            });
+
+            if let Err(error) = &job.result {
+                error_handlers.handle(&mut commands, main_entity.id(), error.clone());
+            }
+            if job_sinks.send_batched {
+                batch.push((main_entity.id(), job.result.clone()));
+            }
+            if job_sinks.trigger_observers {
+                commands.trigger_targets(JobComplete(job.result.clone()), main_entity.id());
+                if let Some(job_type) = job.job_type {
+                    complete_triggers.trigger(
+                        &mut commands,
+                        job_type,
+                        main_entity.id(),
+                        job.result.clone(),
+                    );
+                }
+            }
+            let output_image = match output_images.get(main_entity.id()) {
+                Ok((Some(output), _)) => Some(output.0.clone()),
+                Ok((None, Some(target))) => Some(target.image.clone()),
+                _ => None,
+            };
+            if let (Ok(()), Some(image)) = (&job.result, output_image) {
+                commands.trigger_targets(JobImageReady(image), main_entity.id());
+            }
+            let on_complete = job
+                .job_type
+                .and_then(|job_type| complete_triggers.on_complete(job_type));
+            let output = JobOutput {
+                entity: main_entity.id(),
+                result: job.result.clone(),
+                readbacks: Vec::new(),
+            };
+            if job.pending_readbacks > 0 {
+                awaiting_readbacks.add(
+                    main_entity.id(),
+                    job.pending_readbacks,
+                    on_complete.map(|on_complete| (on_complete, output)),
+                );
+            } else {
+                if let Some(on_complete) = on_complete {
+                    on_complete(&mut commands, output);
+                }
+                if let Some(mut entity) = commands.get_entity(main_entity.id()) {
+                    entity.despawn();
+                }
+            }
+        }
+    }
+
+    if !batch.is_empty() {
+        jobs_completed.send(JobsCompleted(batch));
+    }
+    job_waiters.remove_closed(entities);
+}
+
+/// Jobs run from the render graph that yielded this frame. Their time-outs are
+/// reset in [`JobSet::Cleanup`], as the graph can't insert components itself.
+#[derive(Resource, Default)]
+pub(super) struct ResumedGraphJobs(pub Mutex<Vec<Entity>>);
+
+pub(super) fn resume_graph_jobs(mut resumed: ResMut<ResumedGraphJobs>, mut commands: Commands) {
+    let resumed = mem::take(resumed.0.get_mut().unwrap())
+        .into_iter()
+        .map(|entity| (entity, TimeOutFrames(0)))
+        .collect::<Vec<_>>();
+    commands.try_insert_batch(resumed);
+}
+
+/// Sends [`JobStarted`] to the main world if this is the first time the job runs,
+/// returning whether it is. If so, the caller should also trigger the event in the
+/// render world.
+pub(super) fn send_job_started(
+    entity_ref: EntityRef,
+    main_entity: Option<&MainEntity>,
+    job: &DynamicJob,
+    exec_settings: &JobExecutionSettings,
+    main_world_sender: &JobResultMainWorldSender,
+) -> bool {
+    let is_first_slice = entity_ref
+        .get::<JobContinuation>()
+        .is_none_or(JobContinuation::is_first_slice);
+    if is_first_slice {
+        if let Some(main_entity) = main_entity {
+            let dropped =
+                exec_settings
+                    .channel_failure_policy
+                    .handle_try_send(main_world_sender.0.try_send(JobMainWorldMessage::Started {
+                        main_entity: *main_entity,
+                        label: job.label(),
+                    }));
+            if dropped.is_some() {
+                warn!(
+                    "gigs: the job result channel is full, so `JobStarted` wasn't sent for `{}`",
+                    job.label()
+                );
+            }
+        }
+    }
+    is_first_slice
+}
+
+/// Sends the result of a job that has run, unless it yielded. Returns whether
+/// the job yielded, in which case it stays scheduled, and runs again next frame.
+pub(super) fn send_job_result(
+    entity_ref: EntityRef,
+    main_entity: Option<&MainEntity>,
+    result: Result<(), JobError>,
+    exec_settings: &JobExecutionSettings,
+    job_result_sender: &JobResultSender,
+) -> bool {
+    let yielded = entity_ref
+        .get::<JobContinuation>()
+        .is_some_and(|continuation| result.is_ok() && continuation.take_yielded());
+    if !yielded {
+        exec_settings
+            .channel_failure_policy
+            .handle(job_result_sender.0.send(JobResult {
+                entity: entity_ref.id(),
+                main_entity: main_entity.copied(),
+                result,
+                pending_readbacks: 0,
+                job_type: None,
+            }));
+    }
+    yielded
+}
+
+/// Runs a job, converting a panic into [`JobError::ExecutionFailed`] if `catch_panics`
+/// is enabled. The encoder isn't unwind safe, so it must be discarded if the job panics.
+pub(super) fn run_job(
+    job: &DynamicJob,
+    entity_ref: EntityRef,
+    world: &World,
+    render_device: &RenderDevice,
+    command_encoder: &mut CommandEncoder,
+    catch_panics: bool,
+) -> JobRunOutcome {
+    if !catch_panics {
+        return job.run(entity_ref, world, render_device, command_encoder);
+    }
+
+    catch_unwind(AssertUnwindSafe(|| {
+        job.run(entity_ref, world, render_device, command_encoder)
+    }))
+    .unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        JobRunOutcome::Failed(JobError::execution_failed(format!(
+            "`{}` panicked: {message}",
+            job.label()
+        )))
+    })
+}
+
+/// Runs ready jobs in execution order, calling `run` for each job that may start this
+/// frame within the per-frame limits of [`JobExecutionSettings`], along with whether it
+/// runs as critical. The scheduled position of every job is pushed to `positions`.
+/// Shared by [`run_jobs`] and its mock in tests, which runs without a `RenderDevice`.
+pub(super) fn run_admitted_jobs<'w, T>(
+    jobs: impl Iterator<Item = (EntityRef<'w>, &'w DynamicJob, JobPriority, T)>,
+    exec_settings: &JobExecutionSettings,
+    registered_jobs: &RegisteredJobs,
+    paused: bool,
+    flush: bool,
+    positions: &mut Vec<(Entity, JobScheduledPosition)>,
+    mut run: impl FnMut(EntityRef<'w>, &'w DynamicJob, bool, T),
+) {
+    let mut jobs_per_type = HashMap::<TypeId, u32>::default();
+    let mut jobs_this_frame = 0;
+    let mut bytes_this_frame = 0u64;
+    let encode_start = Instant::now();
+    let over_budget = || {
+        exec_settings
+            .max_encode_millis_per_frame
+            .is_some_and(|max| encode_start.elapsed().as_secs_f32() * 1000.0 >= max)
+    };
+
+    let mut sorted_jobs = jobs.collect::<Vec<_>>();
+    sorted_jobs.sort_by_key(|(entity_ref, _, priority, _)| {
+        scheduled_execution_order(*entity_ref, *priority, exec_settings.priority_aging_rate)
+    });
+
+    for (entity_ref, job, priority, data) in sorted_jobs {
+        let is_critical = priority.is_critical() || flush;
+        let memory_cost = entity_ref.get::<JobMemoryCost>().map_or(0, |cost| cost.0);
+        let over_memory_budget = exec_settings.max_bytes_per_frame.is_some_and(|max| {
+            bytes_this_frame > 0 && bytes_this_frame.saturating_add(memory_cost) > max
+        });
+        if !is_critical
+            && (paused
+                || jobs_this_frame >= exec_settings.max_jobs_per_frame
+                || over_memory_budget
+                || over_budget())
+        {
+            positions.push((entity_ref.id(), JobScheduledPosition(None)));
+            continue;
+        }
+
+        let type_count = jobs_per_type.entry(job.job_type()).or_default();
+        let type_settings = registered_jobs.settings(job.job_type());
+        if !is_critical
+            && type_settings
+                .max_jobs_per_frame
+                .is_some_and(|max| *type_count >= max)
+        {
+            positions.push((entity_ref.id(), JobScheduledPosition(None)));
+            continue;
+        }
+
+        positions.push((entity_ref.id(), JobScheduledPosition(Some(jobs_this_frame))));
+        *type_count += 1;
+        jobs_this_frame += 1;
+        bytes_this_frame = bytes_this_frame.saturating_add(memory_cost);
+
+        run(entity_ref, job, is_critical, data);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn run_jobs(
+    jobs: Query<
+        (EntityRef, Option<&MainEntity>, &DynamicJob, &JobPriority),
+        (
+            With<JobReady>,
+            Without<JobDisabled>,
+            Without<JobDeferred>,
+            Without<CancelJob>,
+            Without<RunInGraph>,
+        ),
+    >,
+    world: &World,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    exec_settings: Res<JobExecutionSettings>,
+    app_focus: Res<AppFocus>,
+    flush: Option<Res<FlushAllJobs>>,
+    registered_jobs: Res<RegisteredJobs>,
+    priority_overrides: Res<JobTypePriorityOverrides>,
+    submit_hooks: Res<JobSubmitHooks>,
+    job_result_sender: Res<JobResultSender>,
+    mut command_encoders: Local<Vec<CommandEncoder>>,
+    mut submitted_jobs: Local<Vec<SubmittedJob>>,
+    mut commands: Commands,
+) {
+    submitted_jobs.clear();
+    let mut positions = Vec::new();
+    let mut resumed = Vec::new();
+    let paused = app_focus.is_paused(&exec_settings);
+    let timestamps = world.get_resource::<JobTimestamps>();
+    let main_world_sender = world.resource::<JobResultMainWorldSender>();
+
+    let jobs = jobs.iter().map(|(entity_ref, main_entity, job, priority)| {
+        let priority = priority_overrides.get(job.job_type()).unwrap_or(*priority);
+        (entity_ref, job, priority, main_entity)
+    });
+    run_admitted_jobs(
+        jobs,
+        &exec_settings,
+        &registered_jobs,
+        paused,
+        flush.is_some(),
+        &mut positions,
+        |entity_ref, job, is_critical, main_entity| {
+            let encoder_label = job.encoder_label(entity_ref);
+            let mut command_encoder =
+                render_device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some(encoder_label.as_ref()),
+                });
+
+            if send_job_started(
+                entity_ref,
+                main_entity,
+                job,
+                &exec_settings,
+                main_world_sender,
+            ) {
+                commands.trigger_targets(JobStarted { label: job.label() }, entity_ref.id());
+            }
+
+            // the group is popped whatever the result, so failed jobs leave it balanced
+            if exec_settings.debug_markers {
+                command_encoder.push_debug_group(&encoder_label);
+            }
+            let timestamp_index =
+                timestamps.and_then(|timestamps| timestamps.begin(&mut command_encoder));
+
+            let result = run_job(
+                job,
+                entity_ref,
+                world,
+                &render_device,
+                &mut command_encoder,
+                exec_settings.catch_panics,
+            )
+            .into_result();
+            if let (Some(Ok(())), Some(timestamps), Some(index)) =
+                (&result, timestamps, timestamp_index)
+            {
+                timestamps.end(&mut command_encoder, index, job.label());
+            }
+            if exec_settings.debug_markers {
+                command_encoder.pop_debug_group();
+            }
+
+            // deferred jobs stay scheduled, and their encoder is dropped unsubmitted
+            let Some(result) = result else {
+                return;
+            };
+
+            if result.is_ok() {
+                command_encoders.push(command_encoder);
+                submitted_jobs.push(SubmittedJob {
+                    entity: entity_ref.id(),
+                    main_entity: main_entity.map(MainEntity::id),
+                    label: job.label(),
+                    critical: is_critical,
+                });
+            }
+
+            if send_job_result(
+                entity_ref,
+                main_entity,
+                result,
+                &exec_settings,
+                &job_result_sender,
+            ) {
+                resumed.push((entity_ref.id(), TimeOutFrames(0)));
+            }
+        },
+    );
+
+    let mut command_buffers = command_encoders
+        .drain(..)
+        .map(|cmd| cmd.finish())
+        .collect::<Vec<_>>();
+
+    // jobs are executed in order of priority, so the commands of critical jobs come first.
+    // Submit hooks may only insert buffers after them
+    let critical_buffers = submitted_jobs.iter().filter(|job| job.critical).count();
+    submit_hooks.pre_submit(world, &submitted_jobs, &mut command_buffers);
+    let timestamp_buffer = timestamps.and_then(|timestamps| timestamps.resolve(&render_device));
+    if exec_settings.split_submissions {
+        let mut background_buffers =
+            command_buffers.split_off(critical_buffers.min(command_buffers.len()));
+        background_buffers.extend(timestamp_buffer);
+        render_queue.submit(command_buffers);
+        if !background_buffers.is_empty() {
+            render_queue.submit(background_buffers);
+        }
+    } else {
+        command_buffers.extend(timestamp_buffer);
+        render_queue.submit(command_buffers);
+    }
+    if let Some(timestamps) = timestamps {
+        timestamps.map();
+    }
+    submit_hooks.post_submit(world);
+
+    if let Some(executed) = world.get_resource::<JobsExecutedThisFrame>() {
+        executed
+            .0
+            .store(submitted_jobs.len() as u32, atomic::Ordering::Relaxed);
+    }
+
+    commands.insert_batch(positions);
+    commands.insert_batch(resumed);
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+    };
+
+    use bevy_ecs::{
+        bundle::Bundle,
+        change_detection::DetectChangesMut,
+        component::Component,
+        entity::Entity,
+        query::{QueryItem, With, Without},
+        schedule::{IntoSystemConfigs, IntoSystemSetConfigs, Schedule},
+        system::{lifetimeless::Read, Commands, Query, Res, ResMut, Resource},
+        world::{EntityRef, World},
+    };
+    use bevy_render::{
+        render_resource::{BufferDescriptor, BufferUsages, CommandEncoder},
+        renderer::{RenderDevice, RenderQueue, WgpuWrapper},
+        sync_world::{MainEntity, RenderEntity},
+        MainWorld,
+    };
+    use bevy_utils::HashMap;
+    use crossbeam_channel::Receiver;
+
+    use super::{
+        cancel_all_jobs, check_job_dependencies, check_job_inputs, erase_jobs, execution_order,
+        find_dependency_cycles, increment_time_out_frames, propagate_priorities,
+        requeue_standing_jobs, run_admitted_jobs, run_jobs, sync_completed_jobs, time_out_jobs,
+        AppFocus, CompletedJobs, DynamicJob, JobIdle, JobMainWorldMessage, JobReady, JobResult,
+        JobResultMainWorldSender, JobResultReceiver, JobResultSender, JobSet,
+        JobStatusMainWorldReceiver, JobStatusMainWorldSender, JobWaiters, NextJobSequence,
+        RegisteredJobs,
+    };
+    use crate::{
+        extract_jobs,
+        input::{JobInput, JobInputItem, JobInputStatus},
+        meta::{
+            extract_job_meta, JobDependencies, JobDisabled, JobMarker, JobPriority, JobSequence,
+            JobTimeout, JobTypePriorityOverrides, StandingJob,
+        },
+        readback::{map_job_readbacks, poll_job_readbacks, JobReadbacks, ReadbackMainWorldSender},
+        CancelAllJobs, GraphicsJob, JobError, JobExecutionSettings, JobRunOutcome, JobSubmitHooks,
+    };
+
+    #[derive(Component, Clone)]
+    struct TestJob;
+
+    #[derive(Component)]
+    struct TestStatus(JobInputStatus);
+
+    struct TestInput;
+
+    impl JobInput<TestJob> for TestInput {
+        type Data = Read<TestStatus>;
+
+        type Item<'a> = ();
+
+        fn status(data: QueryItem<Self::Data>, _world: &World) -> JobInputStatus {
+            data.0
+        }
+
+        fn get<'a>(_data: QueryItem<'a, Self::Data>, _world: &'a World) -> Self::Item<'a> {}
+    }
+
+    impl GraphicsJob for TestJob {
+        type In = TestInput;
+
+        fn run(
+            &self,
+            _world: &World,
+            _render_device: &RenderDevice,
+            _command_encoder: &mut CommandEncoder,
+            (): JobInputItem<Self, Self::In>,
+        ) -> Result<(), JobError> {
+            Ok(())
+        }
+
+        fn run_with_outcome(
+            &self,
+            world: &World,
+            render_device: &RenderDevice,
+            command_encoder: &mut CommandEncoder,
+            input: JobInputItem<Self, Self::In>,
+        ) -> JobRunOutcome {
+            let deferred = world
+                .get_resource::<TestDeferrals>()
+                .is_some_and(|deferrals| {
+                    deferrals
+                        .0
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                        .is_ok()
+                });
+            if deferred {
+                return JobRunOutcome::Defer;
+            }
+            self.run(world, render_device, command_encoder, input)
+                .into()
+        }
+    }
+
+    /// The number of times `TestJob` defers before running, when run by `run_jobs`
+    #[derive(Resource)]
+    struct TestDeferrals(AtomicU32);
+
+    /// The number of jobs run by `mock_run_jobs`
+    #[derive(Resource, Default)]
+    struct RunCount(u32);
+
+    /// How long `mock_run_jobs` takes to "encode" a job
+    #[derive(Component)]
+    struct TestEncodeTime(Duration);
+
+    /// stands in for `run_jobs`, which needs a real `RenderDevice`, admitting and
+    /// ordering jobs the same way
+    fn mock_run_jobs(
+        jobs: Query<(EntityRef, &DynamicJob, &JobPriority), (With<JobReady>, Without<JobDisabled>)>,
+        exec_settings: Res<JobExecutionSettings>,
+        registered_jobs: Res<RegisteredJobs>,
+        job_result_sender: Res<JobResultSender>,
+        mut run_count: ResMut<RunCount>,
+        mut commands: Commands,
+    ) {
+        let mut positions = Vec::new();
+        run_admitted_jobs(
+            jobs.iter()
+                .map(|(entity_ref, job, priority)| (entity_ref, job, *priority, ())),
+            &exec_settings,
+            &registered_jobs,
+            false,
+            false,
+            &mut positions,
+            |entity_ref, _, _, ()| {
+                if let Some(encode_time) = entity_ref.get::<TestEncodeTime>() {
+                    thread::sleep(encode_time.0);
+                }
+                run_count.0 += 1;
+                job_result_sender
+                    .0
+                    .send(JobResult {
+                        entity: entity_ref.id(),
+                        main_entity: entity_ref.get::<MainEntity>().copied(),
+                        result: Ok(()),
+                        pending_readbacks: 0,
+                        job_type: None,
+                    })
+                    .unwrap();
+            },
+        );
+        commands.insert_batch(positions);
+    }
+
+    /// A small xorshift rng, so the test is reproducible without extra dependencies
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn chance(&mut self, one_in: u64) -> bool {
+            self.next() % one_in == 0
+        }
+    }
+
+    /// A render world with the job lifecycle systems, and `mock_run_jobs` in place
+    /// of `run_jobs`
+    fn job_test_world() -> (World, Schedule, Receiver<JobMainWorldMessage>) {
+        job_test_world_with_capacity(None)
+    }
+
+    /// Like `job_test_world`, with a main world channel of the given capacity
+    fn job_test_world_with_capacity(
+        capacity: Option<usize>,
+    ) -> (World, Schedule, Receiver<JobMainWorldMessage>) {
+        let mut world = World::new();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let (main_sender, main_receiver) = match capacity {
+            Some(capacity) => crossbeam_channel::bounded(capacity),
+            None => crossbeam_channel::unbounded(),
+        };
+        world.insert_resource(JobResultSender(sender));
+        world.insert_resource(JobResultReceiver(receiver));
+        world.insert_resource(JobResultMainWorldSender(main_sender));
+        let (status_sender, status_receiver) = crossbeam_channel::unbounded();
+        world.insert_resource(JobStatusMainWorldSender(status_sender));
+        world.insert_resource(JobStatusMainWorldReceiver(status_receiver));
+        // tests that exercise the per-frame limits set them explicitly
+        world.insert_resource(JobExecutionSettings {
+            max_jobs_per_frame: u32::MAX,
+            ..Default::default()
+        });
+        world.init_resource::<AppFocus>();
+        world.init_resource::<RegisteredJobs>();
+        world.init_resource::<NextJobSequence>();
+        world.init_resource::<JobReadbacks>();
+        world.init_resource::<CompletedJobs>();
+        world.init_resource::<RunCount>();
+
+        let mut schedule = Schedule::default();
+        schedule.configure_sets(
+            (
+                JobSet::Setup,
+                JobSet::Check,
+                JobSet::Execute,
+                JobSet::Cleanup,
+            )
+                .chain(),
+        );
+        schedule.add_systems((
+            erase_jobs::<TestJob>.in_set(JobSet::Setup),
+            requeue_standing_jobs::<TestJob>.in_set(JobSet::Setup),
+            check_job_dependencies
+                .in_set(JobSet::Check)
+                .before(check_job_inputs),
+            check_job_inputs.in_set(JobSet::Check),
+            time_out_jobs.in_set(JobSet::Check),
+            mock_run_jobs.in_set(JobSet::Execute),
+            increment_time_out_frames.in_set(JobSet::Cleanup),
+            sync_completed_jobs.in_set(JobSet::Cleanup),
+        ));
+
+        (world, schedule, main_receiver)
+    }
+
+    /// Spawns a job in a `job_test_world` as extraction leaves it in the render world:
+    /// the job component, its `MainEntity`, and its extracted metadata, which defaults
+    /// to a `JobPriority` unless `bundle` overrides it
+    fn spawn_extracted_job(world: &mut World, bundle: impl Bundle) -> Entity {
+        let mut next_main_entity = world.get_resource_or_insert_with(NextMainEntity::default);
+        let main_entity = MainEntity::from(Entity::from_raw(next_main_entity.0));
+        next_main_entity.0 += 1;
+        world
+            .spawn((TestJob, main_entity, JobPriority::default()))
+            .insert(bundle)
+            .id()
+    }
+
+    /// The index of the next fake main world entity for `spawn_extracted_job`
+    #[derive(Resource, Default)]
+    struct NextMainEntity(u32);
+
+    /// Extracts the jobs spawned in the `MainWorld` of a `job_test_world` into it, as
+    /// `ExtractSchedule` does, syncing new job entities by hand. Extracted jobs are
+    /// given the input status `status`.
+    fn extract_test_jobs(world: &mut World, status: JobInputStatus) {
+        let mut main_world = world.remove_resource::<MainWorld>().unwrap_or_default();
+        let unsynced = main_world
+            .query_filtered::<Entity, (With<JobMarker>, Without<RenderEntity>)>()
+            .iter(&main_world)
+            .collect::<Vec<_>>();
+        for main_entity in unsynced {
+            let render_entity = world
+                .spawn((MainEntity::from(main_entity), TestStatus(status)))
+                .id();
+            main_world
+                .entity_mut(main_entity)
+                .insert(RenderEntity::from(render_entity));
+        }
+        world.insert_resource(main_world);
+
+        let mut extract = Schedule::default();
+        extract.add_systems((extract_jobs::<TestJob>, extract_job_meta));
+        extract.run(world);
+        world.resource_mut::<MainWorld>().increment_change_tick();
+    }
+
+    #[test]
+    fn stress_spawn_and_cancel() {
+        const FRAMES: u32 = 200;
+        const SPAWNS_PER_FRAME: u64 = 32;
+
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut jobs = Vec::new();
+        let mut cancelled = Vec::new();
+        let mut results = HashMap::<Entity, Vec<Result<(), JobError>>>::default();
+
+        let statuses = [
+            JobInputStatus::Ready,
+            JobInputStatus::Wait,
+            JobInputStatus::Fail,
+        ];
+
+        for frame in 0..FRAMES * 2 {
+            if frame < FRAMES {
+                for _ in 0..rng.next() % SPAWNS_PER_FRAME {
+                    let status = statuses[(rng.next() % 3) as usize];
+                    let entity = spawn_extracted_job(&mut world, TestStatus(status));
+                    if rng.chance(8) {
+                        world.entity_mut(entity).insert(JobDisabled);
+                    }
+                    jobs.push(entity);
+                }
+
+                for &job in &jobs {
+                    let Ok(mut entity) = world.get_entity_mut(job) else {
+                        continue;
+                    };
+                    if rng.chance(16) {
+                        entity.despawn();
+                        cancelled.push(job);
+                    } else if rng.chance(8) {
+                        entity.insert(TestStatus(statuses[(rng.next() % 3) as usize]));
+                    } else if rng.chance(8) {
+                        entity.remove::<JobDisabled>();
+                    }
+                }
+            } else {
+                // let every remaining job finish or time out
+                for &job in &jobs {
+                    if let Ok(mut entity) = world.get_entity_mut(job) {
+                        entity.remove::<JobDisabled>();
+                    }
+                }
+            }
+
+            schedule.run(&mut world);
+
+            while let Ok(JobMainWorldMessage::Completed(job)) = main_receiver.try_recv() {
+                results.entry(job.entity).or_default().push(job.result);
+            }
+        }
+
+        for job in jobs {
+            let job_results = results.get(&job).map(Vec::len).unwrap_or_default();
+            if cancelled.contains(&job) {
+                assert!(job_results <= 1, "cancelled job completed more than once");
+            } else {
+                assert_eq!(job_results, 1, "job did not complete exactly once");
+                assert!(
+                    world.get_entity(job).is_err(),
+                    "completed job was not despawned"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn standing_jobs_rerun_on_change() {
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+        let job = spawn_extracted_job(&mut world, (TestStatus(JobInputStatus::Ready), StandingJob));
+
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+        assert_eq!(world.resource::<RunCount>().0, 1);
+        assert!(world.entity(job).contains::<JobIdle>());
+
+        world
+            .entity_mut(job)
+            .get_mut::<TestJob>()
+            .unwrap()
+            .set_changed();
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+        assert_eq!(world.resource::<RunCount>().0, 2);
+        assert!(world.entity(job).contains::<JobIdle>());
+        assert!(
+            main_receiver.try_recv().is_err(),
+            "standing job completed after a successful run"
+        );
+    }
+
+    #[test]
+    fn dependencies_completed_earlier_succeed() {
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+        let dependency = spawn_extracted_job(&mut world, TestStatus(JobInputStatus::Ready));
+        let dependency = world.get::<MainEntity>(dependency).unwrap().id();
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+
+        spawn_extracted_job(
+            &mut world,
+            (
+                TestStatus(JobInputStatus::Ready),
+                JobDependencies([dependency].into_iter().collect()),
+            ),
+        );
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+
+        assert_eq!(world.resource::<RunCount>().0, 2);
+        let results = main_receiver
+            .try_iter()
+            .filter_map(|message| match message {
+                JobMainWorldMessage::Completed(job) => Some(job.result),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert!(
+            results.iter().all(Result::is_ok),
+            "dependent of a job completed in an earlier frame failed"
+        );
+    }
+
+    #[test]
+    fn dependencies_on_standing_jobs_fail() {
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+        let dependency =
+            spawn_extracted_job(&mut world, (TestStatus(JobInputStatus::Ready), StandingJob));
+        let dependency = world.get::<MainEntity>(dependency).unwrap().id();
+        spawn_extracted_job(
+            &mut world,
+            (
+                TestStatus(JobInputStatus::Ready),
+                JobDependencies([dependency].into_iter().collect()),
+            ),
+        );
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+
+        let Ok(JobMainWorldMessage::Completed(job)) = main_receiver.try_recv() else {
+            panic!("job depending on a standing job is still waiting");
+        };
+        assert!(matches!(job.result, Err(JobError::DependencyFailed)));
+    }
+
+    #[test]
+    fn full_result_channel_defers_results() {
+        const CAPACITY: usize = 4;
+        const JOBS: usize = 100;
+
+        let (mut world, mut schedule, main_receiver) = job_test_world_with_capacity(Some(CAPACITY));
+        let jobs = (0..JOBS)
+            .map(|_| spawn_extracted_job(&mut world, TestStatus(JobInputStatus::Ready)))
+            .collect::<Vec<_>>();
+
+        // the main world doesn't receive anything for a while
+        for _ in 0..20 {
+            schedule.run(&mut world);
+            assert!(main_receiver.len() <= CAPACITY);
+        }
+        assert_eq!(
+            world.resource::<RunCount>().0,
+            JOBS as u32,
+            "jobs ran again"
+        );
+
+        let mut completed = Vec::new();
+        for _ in 0..JOBS {
+            while let Ok(JobMainWorldMessage::Completed(job)) = main_receiver.try_recv() {
+                completed.push(job.entity);
+            }
+            schedule.run(&mut world);
+        }
+
+        assert_eq!(completed.len(), JOBS, "job did not complete exactly once");
+        assert_eq!(
+            world.resource::<RunCount>().0,
+            JOBS as u32,
+            "jobs ran again"
+        );
+        for job in jobs {
+            assert!(
+                world.get_entity(job).is_err(),
+                "completed job was not despawned"
+            );
+        }
+    }
+
+    /// A render device and queue for tests that need real GPU resources, or `None` if
+    /// no adapter is available, in which case those tests are skipped
+    fn test_render_device() -> Option<(RenderDevice, RenderQueue)> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default()))?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&Default::default(), None)).ok()?;
+        Some((
+            RenderDevice::from(device),
+            RenderQueue(Arc::new(WgpuWrapper::new(queue))),
+        ))
+    }
+
+    #[test]
+    fn deferred_results_count_readbacks_when_sent() {
+        let Some((render_device, _)) = test_render_device() else {
+            eprintln!("skipping `deferred_results_count_readbacks_when_sent`: no GPU adapter");
+            return;
+        };
+        let (mut world, mut schedule, main_receiver) = job_test_world_with_capacity(Some(1));
+        let (readback_sender, readback_receiver) = crossbeam_channel::unbounded();
+        world.insert_resource(ReadbackMainWorldSender(readback_sender));
+        world.insert_resource(render_device.clone());
+        schedule.add_systems((
+            map_job_readbacks
+                .in_set(JobSet::Execute)
+                .after(mock_run_jobs),
+            poll_job_readbacks
+                .in_set(JobSet::Cleanup)
+                .after(sync_completed_jobs),
+        ));
+
+        spawn_extracted_job(&mut world, TestStatus(JobInputStatus::Ready));
+        let job = spawn_extracted_job(&mut world, TestStatus(JobInputStatus::Ready));
+        let main_entity = *world.get::<MainEntity>(job).unwrap();
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        world
+            .resource::<JobReadbacks>()
+            .queue(buffer, main_entity, |_, _, _| {});
+
+        // the job's result is deferred behind the first job's, while its readback finishes
+        for _ in 0..3 {
+            schedule.run(&mut world);
+        }
+        assert_eq!(main_receiver.len(), 1);
+        assert_eq!(readback_receiver.len(), 1, "readback didn't finish");
+
+        main_receiver.try_recv().unwrap();
+        schedule.run(&mut world);
+        let Ok(JobMainWorldMessage::Completed(result)) = main_receiver.try_recv() else {
+            panic!("deferred result wasn't sent");
+        };
+        assert_eq!(result.main_entity, Some(main_entity));
+        assert_eq!(
+            result.pending_readbacks, 0,
+            "the main world would wait for a readback it already received"
+        );
+    }
+
+    #[test]
+    fn deferred_jobs_stay_scheduled() {
+        let Some((render_device, render_queue)) = test_render_device() else {
+            eprintln!("skipping `deferred_jobs_stay_scheduled`: no GPU adapter");
+            return;
+        };
+        let (mut world, _, main_receiver) = job_test_world();
+        world.insert_resource(render_device);
+        world.insert_resource(render_queue);
+        world.insert_resource(TestDeferrals(AtomicU32::new(2)));
+        world.init_resource::<JobTypePriorityOverrides>();
+        let submitted = Arc::new(Mutex::new(Vec::new()));
+        let hook_submitted = submitted.clone();
+        world
+            .get_resource_or_insert_with(JobSubmitHooks::default)
+            .add_pre_submit(move |_, jobs, command_buffers| {
+                assert_eq!(jobs.len(), command_buffers.len());
+                hook_submitted.lock().unwrap().push(jobs.len());
+            });
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                erase_jobs::<TestJob>,
+                check_job_inputs,
+                run_jobs,
+                increment_time_out_frames,
+                sync_completed_jobs,
+            )
+                .chain(),
+        );
+        let job = spawn_extracted_job(&mut world, TestStatus(JobInputStatus::Ready));
+
+        for _ in 0..2 {
+            schedule.run(&mut world);
+        }
+        assert_eq!(
+            *submitted.lock().unwrap(),
+            [0, 0],
+            "deferred job was submitted"
+        );
+        assert!(
+            world.entity(job).contains::<JobReady>(),
+            "deferred job isn't scheduled"
+        );
+        assert!(
+            !main_receiver
+                .try_iter()
+                .any(|message| matches!(message, JobMainWorldMessage::Completed(_))),
+            "deferred job completed"
+        );
+
+        schedule.run(&mut world);
+        assert_eq!(*submitted.lock().unwrap(), [0, 0, 1]);
+        let completed = main_receiver.try_iter().find_map(|message| match message {
+            JobMainWorldMessage::Completed(job) => Some(job.result),
+            _ => None,
+        });
+        assert!(
+            completed.is_some_and(|result| result.is_ok()),
+            "job didn't complete once it stopped deferring"
+        );
+    }
+
+    #[test]
+    fn full_result_channel_defers_failed_results() {
+        const CAPACITY: usize = 2;
+        const JOBS: usize = 10;
+
+        let (mut world, mut schedule, main_receiver) = job_test_world_with_capacity(Some(CAPACITY));
+        world.resource_mut::<JobExecutionSettings>().time_out_frames = 1;
+        let jobs = (0..JOBS)
+            .map(|_| spawn_extracted_job(&mut world, TestStatus(JobInputStatus::Wait)))
+            .collect::<Vec<_>>();
+
+        // the jobs time out, and are despawned, while their results are deferred
+        for _ in 0..5 {
+            schedule.run(&mut world);
+        }
+
+        let mut completed = Vec::new();
+        for _ in 0..JOBS {
+            while let Ok(JobMainWorldMessage::Completed(job)) = main_receiver.try_recv() {
+                assert!(matches!(job.result, Err(JobError::TimedOut)));
+                completed.push(job.entity);
+            }
+            schedule.run(&mut world);
+        }
+
+        assert_eq!(completed.len(), JOBS, "job did not complete exactly once");
+        for job in jobs {
+            assert!(
+                world.get_entity(job).is_err(),
+                "timed out job was respawned"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_budget_defers_slow_jobs() {
+        const SLOW_JOBS: u32 = 8;
+
+        let (mut world, mut schedule, _main_receiver) = job_test_world();
+        world
+            .resource_mut::<JobExecutionSettings>()
+            .max_encode_millis_per_frame = Some(5.0);
+
+        for _ in 0..2 {
+            spawn_extracted_job(
+                &mut world,
+                (
+                    JobPriority::critical(),
+                    TestStatus(JobInputStatus::Ready),
+                    TestEncodeTime(Duration::from_millis(6)),
+                ),
+            );
+        }
+        for _ in 0..SLOW_JOBS {
+            spawn_extracted_job(
+                &mut world,
+                (
+                    TestStatus(JobInputStatus::Ready),
+                    TestEncodeTime(Duration::from_millis(1)),
+                ),
+            );
+        }
+
+        while world.resource::<RunCount>().0 == 0 {
+            schedule.run(&mut world);
+        }
+        assert_eq!(
+            world.resource::<RunCount>().0,
+            2,
+            "critical jobs ignore the budget, and other jobs wait once it's exceeded"
+        );
+
+        schedule.run(&mut world);
+        let slow_jobs_run = world.resource::<RunCount>().0 - 2;
+        assert!(
+            slow_jobs_run > 0 && slow_jobs_run < SLOW_JOBS,
+            "slow jobs are admitted until the budget is exceeded"
+        );
+
+        for _ in 0..SLOW_JOBS {
+            schedule.run(&mut world);
+        }
+        assert_eq!(world.resource::<RunCount>().0, SLOW_JOBS + 2);
+    }
+
+    #[test]
+    fn aging_prevents_starvation() {
+        const MAX_JOBS_PER_FRAME: u32 = 4;
+
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+        {
+            let mut settings = world.resource_mut::<JobExecutionSettings>();
+            settings.max_jobs_per_frame = MAX_JOBS_PER_FRAME;
+            settings.priority_aging_rate = 1;
+            settings.time_out_frames = JobTimeout::NEVER.0;
+        }
+
+        let low_priority = spawn_extracted_job(
+            &mut world,
+            (
+                JobPriority::non_critical::<1>(),
+                TestStatus(JobInputStatus::Ready),
+            ),
+        );
+
+        for frame in 0..100 {
+            // a steady stream of fresh high-priority jobs, more than can run each frame
+            for _ in 0..MAX_JOBS_PER_FRAME * 2 {
+                spawn_extracted_job(
+                    &mut world,
+                    (
+                        JobPriority::non_critical::<10>(),
+                        TestStatus(JobInputStatus::Ready),
+                    ),
+                );
+            }
+            schedule.run(&mut world);
+
+            let ran = main_receiver.try_iter().any(|message| {
+                matches!(message, JobMainWorldMessage::Completed(job) if job.entity == low_priority)
+            });
+            if ran {
+                assert!(frame > 0, "low-priority job ran before it aged");
+                return;
+            }
+        }
+
+        panic!("low-priority job starved");
+    }
+
+    #[test]
+    fn extracted_jobs_time_out() {
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+        world.resource_mut::<JobExecutionSettings>().time_out_frames = 2;
+
+        let main_entity = world
+            .get_resource_or_insert_with(MainWorld::default)
+            .spawn((TestJob, JobMarker))
+            .id();
+
+        for _ in 0..4 {
+            extract_test_jobs(&mut world, JobInputStatus::Wait);
+            schedule.run(&mut world);
+        }
+
+        let Ok(JobMainWorldMessage::Completed(job)) = main_receiver.try_recv() else {
+            panic!("extracted job didn't time out");
+        };
+        assert_eq!(job.main_entity, Some(MainEntity::from(main_entity)));
+        assert!(matches!(job.result, Err(JobError::TimedOut)));
+    }
+
+    #[test]
+    fn cancel_all_cancels_extracted_jobs() {
+        let (mut world, mut schedule, main_receiver) = job_test_world();
+        schedule.add_systems(cancel_all_jobs.in_set(JobSet::Check));
+
+        let mut main_world = world.get_resource_or_insert_with(MainWorld::default);
+        let main_entities = [(); 3].map(|_| main_world.spawn((TestJob, JobMarker)).id());
+        extract_test_jobs(&mut world, JobInputStatus::Wait);
+        schedule.run(&mut world);
+
+        world.insert_resource(CancelAllJobs);
+        schedule.run(&mut world);
+
+        let cancelled = main_receiver
+            .try_iter()
+            .filter_map(|message| match message {
+                JobMainWorldMessage::Completed(JobResult {
+                    main_entity: Some(main_entity),
+                    result: Err(JobError::Cancelled),
+                    ..
+                }) => Some(main_entity.id()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(cancelled.len(), main_entities.len());
+        assert!(main_entities
+            .iter()
+            .all(|entity| cancelled.contains(entity)));
+    }
+
+    #[test]
+    fn equal_priorities_run_in_order() {
+        let mut world = World::new();
+        world.init_resource::<NextJobSequence>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(erase_jobs::<TestJob>);
+
+        let mut spawned = Vec::new();
+        for _ in 0..2 {
+            for _ in 0..50 {
+                spawned.push(world.spawn((TestJob, JobPriority::default())).id());
+            }
+            schedule.run(&mut world);
+        }
+
+        let mut jobs = world
+            .query::<(Entity, &JobPriority, &JobSequence)>()
+            .iter(&world)
+            .map(|(entity, priority, sequence)| (entity, *priority, *sequence))
+            .collect::<Vec<_>>();
+        jobs.sort_by_key(|(_, priority, sequence)| execution_order(*priority, Some(sequence)));
+
+        let order = jobs.iter().map(|(entity, ..)| *entity).collect::<Vec<_>>();
+        assert_eq!(order, spawned, "older jobs win ties");
+    }
+
+    #[test]
+    fn waiting_on_finished_jobs_resolves() {
+        let mut world = World::new();
+        world.init_resource::<JobWaiters>();
+        let completed = world.spawn_empty().id();
+        let despawned = world.spawn_empty().id();
+        world.despawn(despawned);
+
+        world
+            .resource_mut::<JobWaiters>()
+            .complete(completed, &Ok(()));
+
+        let (sender, receiver) = async_channel::bounded(1);
+        JobWaiters::add(&mut world, completed, sender);
+        assert!(matches!(receiver.try_recv(), Ok(Ok(()))));
+
+        let (sender, receiver) = async_channel::bounded(1);
+        JobWaiters::add(&mut world, despawned, sender);
+        assert!(matches!(receiver.try_recv(), Ok(Err(JobError::Cancelled))));
+    }
+
+    #[test]
+    fn priority_propagation() {
+        let mut world = World::new();
+        let [a, b, c, d] = [(); 4].map(|_| world.spawn_empty().id());
+        let on_d = JobDependencies([d].into_iter().collect());
+        let on_b_c = JobDependencies([b, c].into_iter().collect());
+        let graph = [
+            (a, (JobPriority::non_critical::<5>(), Some(&on_b_c))),
+            (b, (JobPriority::non_critical::<1>(), Some(&on_d))),
+            (c, (JobPriority::non_critical::<1>(), Some(&on_d))),
+            (d, (JobPriority::non_critical::<1>(), None)),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        let priorities = propagate_priorities(&graph);
+        assert_eq!(priorities[&a], JobPriority::non_critical::<5>());
+        assert_eq!(priorities[&b], JobPriority::non_critical::<6>());
+        assert_eq!(
+            priorities[&d],
+            JobPriority::non_critical::<8>(),
+            "jobs are only counted once in diamond-shaped graphs"
+        );
+
+        let graph = [
+            (
+                a,
+                (JobPriority::non_critical::<{ u32::MAX }>(), Some(&on_d)),
+            ),
+            (c, (JobPriority::critical(), Some(&on_d))),
+            (d, (JobPriority::non_critical::<1>(), None)),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        let priorities = propagate_priorities(&graph);
+        assert!(priorities[&d].is_critical());
+
+        let graph = [
+            (
+                a,
+                (JobPriority::non_critical::<{ u32::MAX }>(), Some(&on_d)),
+            ),
+            (d, (JobPriority::non_critical::<1>(), None)),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        let priorities = propagate_priorities(&graph);
+        assert_eq!(
+            priorities[&d],
+            JobPriority::non_critical::<{ u32::MAX }>(),
+            "priorities saturate"
+        );
+    }
+
+    #[test]
+    fn dependency_cycles() {
+        let mut world = World::new();
+        let [a, b, c, d] = [(); 4].map(|_| world.spawn_empty().id());
+        let dependencies = [
+            (a, JobDependencies([b].into_iter().collect())),
+            (b, JobDependencies([c].into_iter().collect())),
+            (c, JobDependencies([a].into_iter().collect())),
+            (d, JobDependencies([a].into_iter().collect())),
+        ];
+        let graph = dependencies
+            .iter()
+            .map(|(job, dependencies)| (*job, dependencies))
+            .collect::<HashMap<_, _>>();
+
+        let cyclic = find_dependency_cycles(&graph);
+        assert!(cyclic.contains(&a) && cyclic.contains(&b) && cyclic.contains(&c));
+        assert!(
+            !cyclic.contains(&d),
+            "jobs depending on a cycle aren't part of it"
+        );
+    }
 }
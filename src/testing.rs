@@ -0,0 +1,147 @@
+//! Utilities for testing apps that use graphics jobs.
+
+use bevy_app::App;
+use bevy_ecs::{
+    entity::Entity,
+    query::{Or, With},
+};
+
+use crate::{
+    job_sub_app_mut,
+    meta::JobMarker,
+    readback::{JobReadbacks, ReadbackMainWorldReceiver},
+    runner::{
+        DynamicJob, JobReady, JobResultMainWorldReceiver, JobResultReceiver,
+        JobStatusMainWorldReceiver,
+    },
+};
+
+/// Asserts that no graphics jobs are left pending in `app`, for example at the end
+/// of a test. This checks for job entities in both the main world and the job sub-app,
+/// for results or status updates that haven't been delivered yet, and for unfinished
+/// readbacks. Useful for catching jobs leaked by lifecycle bugs.
+///
+/// # Panics
+///
+/// Panics if any jobs are pending, with a message describing what was found.
+#[track_caller]
+pub fn assert_no_pending_jobs(app: &mut App) {
+    let mut pending = Vec::new();
+
+    let main_world = app.world_mut();
+    let main_jobs = main_world
+        .query_filtered::<Entity, With<JobMarker>>()
+        .iter(main_world)
+        .count();
+    if main_jobs > 0 {
+        pending.push(format!("{main_jobs} job entities in the main world"));
+    }
+    if main_world
+        .get_resource::<JobResultMainWorldReceiver>()
+        .is_some_and(|receiver| !receiver.0.is_empty())
+    {
+        pending.push("undelivered job results in the main world".to_string());
+    }
+    if main_world
+        .get_resource::<JobStatusMainWorldReceiver>()
+        .is_some_and(|receiver| !receiver.0.is_empty())
+    {
+        pending.push("undelivered job input statuses in the main world".to_string());
+    }
+    if main_world
+        .get_resource::<ReadbackMainWorldReceiver>()
+        .is_some_and(|receiver| !receiver.0.is_empty())
+    {
+        pending.push("undelivered readbacks in the main world".to_string());
+    }
+
+    if let Some(sub_app) = job_sub_app_mut(app) {
+        let render_world = sub_app.world_mut();
+        let render_jobs = render_world
+            .query_filtered::<Entity, Or<(With<JobMarker>, With<DynamicJob>, With<JobReady>)>>()
+            .iter(render_world)
+            .count();
+        if render_jobs > 0 {
+            pending.push(format!("{render_jobs} job entities in the job sub-app"));
+        }
+        if render_world
+            .get_resource::<JobResultReceiver>()
+            .is_some_and(|receiver| !receiver.0.is_empty())
+        {
+            pending.push("unsynced job results in the job sub-app".to_string());
+        }
+        if render_world
+            .get_resource::<JobReadbacks>()
+            .is_some_and(|readbacks| !readbacks.is_empty())
+        {
+            pending.push("unfinished readbacks in the job sub-app".to_string());
+        }
+    }
+
+    assert!(
+        pending.is_empty(),
+        "gigs: found pending jobs: {}",
+        pending.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use bevy_app::App;
+    use bevy_ecs::{component::Component, world::World};
+    use bevy_render::{render_resource::CommandEncoder, renderer::RenderDevice};
+
+    use super::assert_no_pending_jobs;
+    use crate::{
+        input::JobInputItem,
+        meta::{JobMarker, JobTimeout},
+        GraphicsJob, GraphicsJobsPlugin, InitGraphicsJobExt, JobError,
+    };
+
+    #[derive(Component, Clone)]
+    struct TestJob;
+
+    impl GraphicsJob for TestJob {
+        type In = ();
+
+        fn run(
+            &self,
+            _world: &World,
+            _render_device: &RenderDevice,
+            _command_encoder: &mut CommandEncoder,
+            _input: JobInputItem<Self, Self::In>,
+        ) -> Result<(), JobError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn completed_jobs_are_not_pending() {
+        // without a render sub-app, jobs fail as they're spawned
+        let mut app = App::new();
+        app.add_plugins(GraphicsJobsPlugin::default())
+            .init_graphics_job::<TestJob>();
+        app.finish();
+        app.cleanup();
+        app.world_mut().spawn(TestJob);
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_no_pending_jobs(&mut app);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 job entities in the main world")]
+    fn stuck_jobs_are_pending() {
+        // nothing runs the job, so it waits forever
+        let mut app = App::new();
+        app.world_mut()
+            .spawn((TestJob, JobMarker, JobTimeout::NEVER));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_no_pending_jobs(&mut app);
+    }
+}
@@ -0,0 +1,120 @@
+//! Adapting the number of jobs executed each frame to the measured frame time.
+
+use bevy_ecs::system::{Local, Res, ResMut, Resource};
+use bevy_utils::Instant;
+
+use crate::JobExecutionSettings;
+
+/// The number of frames between adjustments of the job limit, so that the effect
+/// of each adjustment shows up in the frame time before the next one
+const ADJUST_INTERVAL: u32 = 8;
+
+/// The weight of each new frame time in the smoothed frame time
+const SMOOTHING: f32 = 0.1;
+
+/// A main-world resource that adjusts [`JobExecutionSettings::max_jobs_per_frame`]
+/// towards a target frame time: the limit is raised by one while frames are faster
+/// than the target, and halved while they're slower. Frame times within `hysteresis`
+/// of the target leave the limit unchanged, so it doesn't oscillate around the target.
+///
+/// Enable this with [`GraphicsJobsPlugin::with_throttle`](crate::GraphicsJobsPlugin::with_throttle),
+/// or by inserting the resource, and remove it to stop adjusting the limit. While
+/// enabled, any changes made to `max_jobs_per_frame` by hand are overwritten.
+///
+/// Note: jobs with [`Priority::Critical`](crate::meta::Priority::Critical) ignore
+/// `max_jobs_per_frame`, so they're never deferred by the throttle, even under load.
+#[derive(Resource, Copy, Clone, Debug)]
+pub struct JobThrottle {
+    /// The frame time to aim for, in milliseconds
+    pub target_frame_millis: f32,
+    /// The fraction of the target that frame times may differ by before the limit
+    /// is adjusted
+    pub hysteresis: f32,
+    /// The lowest the limit may be lowered to
+    pub min_jobs_per_frame: u32,
+    /// The highest the limit may be raised to
+    pub max_jobs_per_frame: u32,
+}
+
+impl Default for JobThrottle {
+    fn default() -> Self {
+        Self {
+            target_frame_millis: 1000.0 / 60.0,
+            hysteresis: 0.1,
+            min_jobs_per_frame: 1,
+            max_jobs_per_frame: 64,
+        }
+    }
+}
+
+impl JobThrottle {
+    /// Returns the job limit to use given the current limit and the smoothed frame time
+    fn next_max_jobs(&self, current: u32, frame_millis: f32) -> u32 {
+        let next = if frame_millis > self.target_frame_millis * (1.0 + self.hysteresis) {
+            current / 2
+        } else if frame_millis < self.target_frame_millis * (1.0 - self.hysteresis) {
+            current.saturating_add(1)
+        } else {
+            current
+        };
+        next.clamp(self.min_jobs_per_frame, self.max_jobs_per_frame)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ThrottleState {
+    last_frame: Option<Instant>,
+    frame_millis: Option<f32>,
+    frames_since_adjust: u32,
+}
+
+pub(crate) fn throttle_jobs(
+    throttle: Res<JobThrottle>,
+    mut exec_settings: ResMut<JobExecutionSettings>,
+    mut state: Local<ThrottleState>,
+) {
+    let now = Instant::now();
+    let Some(last_frame) = state.last_frame.replace(now) else {
+        return;
+    };
+
+    let elapsed = now.duration_since(last_frame).as_secs_f32() * 1000.0;
+    let frame_millis = match state.frame_millis {
+        Some(smoothed) => smoothed + (elapsed - smoothed) * SMOOTHING,
+        None => elapsed,
+    };
+    state.frame_millis = Some(frame_millis);
+
+    state.frames_since_adjust += 1;
+    if state.frames_since_adjust < ADJUST_INTERVAL {
+        return;
+    }
+    state.frames_since_adjust = 0;
+
+    let next = throttle.next_max_jobs(exec_settings.max_jobs_per_frame, frame_millis);
+    if next != exec_settings.max_jobs_per_frame {
+        exec_settings.max_jobs_per_frame = next;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::JobThrottle;
+
+    #[test]
+    fn throttle_adjusts_within_bounds() {
+        let throttle = JobThrottle {
+            target_frame_millis: 10.0,
+            hysteresis: 0.1,
+            min_jobs_per_frame: 2,
+            max_jobs_per_frame: 8,
+        };
+
+        assert_eq!(throttle.next_max_jobs(4, 5.0), 5);
+        assert_eq!(throttle.next_max_jobs(8, 5.0), 8);
+        assert_eq!(throttle.next_max_jobs(4, 10.5), 4);
+        assert_eq!(throttle.next_max_jobs(8, 20.0), 4);
+        assert_eq!(throttle.next_max_jobs(3, 20.0), 2);
+        assert_eq!(throttle.next_max_jobs(32, 10.0), 8);
+    }
+}
@@ -0,0 +1,330 @@
+//! GPU timing of individual graphics jobs, using timestamp queries.
+
+use core::{mem, time::Duration};
+use std::sync::Mutex;
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::{
+    schedule::IntoSystemConfigs,
+    system::{Commands, Local, Res, ResMut, Resource},
+};
+use bevy_render::{
+    render_resource::{
+        Buffer, BufferDescriptor, BufferUsages, CommandEncoder, CommandEncoderDescriptor,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    Render,
+};
+use bevy_utils::{tracing::warn, HashMap};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use disqualified::ShortName;
+use wgpu::{
+    BufferAsyncError, CommandBuffer, Features, Maintain, MapMode, QuerySet, QuerySetDescriptor,
+    QueryType, QUERY_SET_MAX_QUERIES, QUERY_SIZE,
+};
+
+use crate::{job_sub_app_mut, GraphicsJob, JobCleanupSet, JobExecutionSettings, JobSet};
+
+/// The weight of each new sample in the rolling average of a job's duration
+const SMOOTHING: f64 = 0.1;
+
+/// A plugin that measures how long each job takes to execute on the GPU, and records
+/// the results in the [`JobTimings`] resource. Must be added after
+/// [`GraphicsJobsPlugin`](crate::GraphicsJobsPlugin).
+///
+/// This requires the [`TIMESTAMP_QUERY`](Features::TIMESTAMP_QUERY) and
+/// [`TIMESTAMP_QUERY_INSIDE_ENCODERS`](Features::TIMESTAMP_QUERY_INSIDE_ENCODERS)
+/// features. If they aren't supported, a warning is logged and no timings are recorded.
+///
+/// Up to [`JobExecutionSettings::max_jobs_per_frame`] jobs are timed each frame. Frames
+/// where the previous frame's timings haven't been read back yet are skipped.
+pub struct JobTimestampsPlugin;
+
+impl Plugin for JobTimestampsPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        app.init_resource::<JobTimings>()
+            .insert_resource(JobTimingsMainWorldReceiver(receiver))
+            .add_systems(Update, sync_job_timings_main_world);
+
+        let Some(render_app) = job_sub_app_mut(app) else {
+            return;
+        };
+
+        render_app
+            .insert_resource(JobTimingsMainWorldSender(sender))
+            .add_systems(
+                Render,
+                (
+                    prepare_job_timestamps.in_set(JobSet::Setup),
+                    poll_job_timestamps.in_set(JobCleanupSet::Readbacks),
+                ),
+            );
+    }
+}
+
+/// The GPU execution time of a type of job, as measured by [`JobTimestampsPlugin`].
+#[derive(Copy, Clone, Default, Debug)]
+pub struct JobTiming {
+    /// A rolling average of the job's duration
+    pub average: Duration,
+    /// The duration of the most recently timed job
+    pub last: Duration,
+    /// The number of jobs timed so far
+    pub samples: u32,
+}
+
+impl JobTiming {
+    fn add_sample(&mut self, duration: Duration) {
+        self.average = if self.samples == 0 {
+            duration
+        } else {
+            self.average.mul_f64(1.0 - SMOOTHING) + duration.mul_f64(SMOOTHING)
+        };
+        self.last = duration;
+        self.samples = self.samples.saturating_add(1);
+    }
+}
+
+/// A main-world resource holding the GPU execution time of each type of job, keyed by
+/// its label. Timings arrive a few frames after the jobs complete. Requires
+/// [`JobTimestampsPlugin`].
+#[derive(Resource, Default, Debug)]
+pub struct JobTimings(HashMap<&'static str, (ShortName<'static>, JobTiming)>);
+
+impl JobTimings {
+    /// The timing of jobs of type `J`, if any have been timed.
+    pub fn get<J: GraphicsJob>(&self) -> Option<&JobTiming> {
+        self.0.get(J::label().original()).map(|(_, timing)| timing)
+    }
+
+    /// Iterates over the labels and timings of every type of job that has been timed.
+    pub fn iter(&self) -> impl Iterator<Item = (ShortName<'static>, &JobTiming)> {
+        self.0.values().map(|(label, timing)| (*label, timing))
+    }
+}
+
+#[derive(Resource)]
+struct JobTimingsMainWorldSender(Sender<Vec<(ShortName<'static>, Duration)>>);
+#[derive(Resource)]
+struct JobTimingsMainWorldReceiver(Receiver<Vec<(ShortName<'static>, Duration)>>);
+
+/// Render-world timestamp queries for jobs executed this frame. Each job is given
+/// two consecutive queries, written at the start and end of its command encoder.
+#[derive(Resource)]
+pub(crate) struct JobTimestamps {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    /// The number of jobs that may be timed each frame
+    capacity: u32,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    frame: Mutex<TimestampFrame>,
+}
+
+#[derive(Default)]
+struct TimestampFrame {
+    /// The labels of jobs timed this frame, or `None` for jobs that failed
+    labels: Vec<Option<ShortName<'static>>>,
+    /// The labels of jobs being read back, and the mapping status of the readback buffer
+    mapping: Option<(
+        Vec<Option<ShortName<'static>>>,
+        Receiver<Result<(), BufferAsyncError>>,
+    )>,
+}
+
+impl JobTimestamps {
+    fn new(render_device: &RenderDevice, period: f32, capacity: u32) -> Self {
+        let query_count = capacity * 2;
+        let size = query_count as u64 * QUERY_SIZE as u64;
+
+        Self {
+            query_set: render_device
+                .wgpu_device()
+                .create_query_set(&QuerySetDescriptor {
+                    label: Some("job_timestamp_query_set"),
+                    ty: QueryType::Timestamp,
+                    count: query_count,
+                }),
+            resolve_buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("job_timestamp_resolve_buffer"),
+                size,
+                usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("job_timestamp_readback_buffer"),
+                size,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            capacity,
+            period,
+            frame: Mutex::new(TimestampFrame::default()),
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.frame.lock().unwrap().mapping.is_some()
+    }
+
+    /// Writes the starting timestamp for a job, returning its index if
+    /// there's room for it this frame.
+    pub fn begin(&self, command_encoder: &mut CommandEncoder) -> Option<u32> {
+        let mut frame = self.frame.lock().unwrap();
+        if frame.mapping.is_some() || frame.labels.len() as u32 >= self.capacity {
+            return None;
+        }
+
+        let index = frame.labels.len() as u32;
+        frame.labels.push(None);
+        command_encoder.write_timestamp(&self.query_set, index * 2);
+        Some(index)
+    }
+
+    /// Writes the ending timestamp for a job that ran successfully.
+    pub fn end(&self, command_encoder: &mut CommandEncoder, index: u32, label: ShortName<'static>) {
+        command_encoder.write_timestamp(&self.query_set, index * 2 + 1);
+        self.frame.lock().unwrap().labels[index as usize] = Some(label);
+    }
+
+    /// Encodes the commands to resolve this frame's timestamps, if any jobs were timed.
+    pub fn resolve(&self, render_device: &RenderDevice) -> Option<CommandBuffer> {
+        let frame = self.frame.lock().unwrap();
+        if frame.labels.iter().all(Option::is_none) {
+            return None;
+        }
+
+        let query_count = frame.labels.len() as u32 * 2;
+        let mut command_encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("job_timestamp_resolve"),
+        });
+        command_encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+        command_encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            query_count as u64 * QUERY_SIZE as u64,
+        );
+        Some(command_encoder.finish())
+    }
+
+    /// Begins reading back this frame's timestamps. Must be called after
+    /// the commands from [`resolve`](Self::resolve) have been submitted.
+    pub fn map(&self) {
+        let mut frame = self.frame.lock().unwrap();
+        let labels = mem::take(&mut frame.labels);
+        if labels.iter().all(Option::is_none) {
+            return;
+        }
+
+        let (sender, mapped) = crossbeam_channel::bounded(1);
+        self.readback_buffer
+            .slice(..labels.len() as u64 * 2 * QUERY_SIZE as u64)
+            .map_async(MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        frame.mapping = Some((labels, mapped));
+    }
+}
+
+fn prepare_job_timestamps(
+    timestamps: Option<Res<JobTimestamps>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    exec_settings: Res<JobExecutionSettings>,
+    mut warned: Local<bool>,
+    mut commands: Commands,
+) {
+    let required = Features::TIMESTAMP_QUERY | Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+    if !render_device.features().contains(required) {
+        if !*warned {
+            warn!("`JobTimestampsPlugin` requires the `TIMESTAMP_QUERY` and `TIMESTAMP_QUERY_INSIDE_ENCODERS` features, which aren't supported. No job timings will be recorded.");
+            *warned = true;
+        }
+        return;
+    }
+
+    let capacity = exec_settings
+        .max_jobs_per_frame
+        .clamp(1, QUERY_SET_MAX_QUERIES / 2);
+
+    // grow (or shrink) the query set to fit, once no readback is in progress
+    if timestamps
+        .as_ref()
+        .is_some_and(|timestamps| timestamps.capacity == capacity || timestamps.is_busy())
+    {
+        return;
+    }
+
+    commands.insert_resource(JobTimestamps::new(
+        &render_device,
+        render_queue.get_timestamp_period(),
+        capacity,
+    ));
+}
+
+fn poll_job_timestamps(
+    timestamps: Option<ResMut<JobTimestamps>>,
+    render_device: Res<RenderDevice>,
+    main_world_sender: Res<JobTimingsMainWorldSender>,
+    exec_settings: Res<JobExecutionSettings>,
+) {
+    let Some(mut timestamps) = timestamps else {
+        return;
+    };
+    let timestamps = &mut *timestamps;
+    let frame = timestamps.frame.get_mut().unwrap();
+    let Some((labels, mapped)) = &frame.mapping else {
+        return;
+    };
+
+    render_device.wgpu_device().poll(Maintain::Poll);
+
+    match mapped.try_recv() {
+        Ok(Ok(())) => {
+            let slice = timestamps
+                .readback_buffer
+                .slice(..labels.len() as u64 * 2 * QUERY_SIZE as u64);
+            let bytes = slice.get_mapped_range().to_vec();
+            timestamps.readback_buffer.unmap();
+
+            let timings = labels
+                .iter()
+                .zip(bytes.chunks_exact(2 * QUERY_SIZE as usize))
+                .filter_map(|(label, chunk)| {
+                    let start = u64::from_le_bytes(chunk[..8].try_into().unwrap());
+                    let end = u64::from_le_bytes(chunk[8..].try_into().unwrap());
+                    let nanos = end.saturating_sub(start) as f64 * timestamps.period as f64;
+                    label.map(|label| (label, Duration::from_nanos(nanos as u64)))
+                })
+                .collect::<Vec<_>>();
+
+            exec_settings
+                .channel_failure_policy
+                .handle(main_world_sender.0.send(timings));
+            frame.mapping = None;
+        }
+        Err(TryRecvError::Empty) => {}
+        Ok(Err(_)) | Err(TryRecvError::Disconnected) => frame.mapping = None,
+    }
+}
+
+fn sync_job_timings_main_world(
+    receiver: Res<JobTimingsMainWorldReceiver>,
+    mut job_timings: ResMut<JobTimings>,
+) {
+    while let Ok(timings) = receiver.0.try_recv() {
+        for (label, duration) in timings {
+            job_timings
+                .0
+                .entry(label.original())
+                .or_insert_with(|| (label, JobTiming::default()))
+                .1
+                .add_sample(duration);
+        }
+    }
+}
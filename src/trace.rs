@@ -0,0 +1,100 @@
+//! Tracing of executed jobs to a file, for offline analysis. Requires the `trace` feature.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use bevy_app::{App, Plugin};
+use bevy_utils::tracing::error;
+
+use crate::{job_sub_app_mut, JobSubmitHooks, SubmittedJob};
+
+/// A plugin that writes a summary of the jobs submitted each frame to a file, as
+/// newline-delimited JSON. Each line has the form:
+///
+/// ```json
+/// {"frame":12,"jobs":[{"label":"TerrainGenJob","entity":"5v1","main_entity":"9v1"}]}
+/// ```
+///
+/// Only jobs that executed successfully are included, and frames without any
+/// jobs are skipped. Since command buffers are opaque, the commands encoded by
+/// each job aren't captured. The file is flushed after every frame, so the trace
+/// is complete up to the last frame even if the app crashes. Must be added after
+/// [`GraphicsJobsPlugin`](crate::GraphicsJobsPlugin).
+///
+/// Note: jobs run from a [`GraphicsJobNode`](crate::graph::GraphicsJobNode) aren't
+/// submitted through [`JobSubmitHooks`], so they don't appear in the trace.
+pub struct JobTracePlugin {
+    /// The file to write the trace to. It will be overwritten if it exists.
+    pub path: PathBuf,
+}
+
+impl Plugin for JobTracePlugin {
+    fn build(&self, app: &mut App) {
+        let file = match File::create(&self.path) {
+            Ok(file) => file,
+            Err(err) => {
+                error!(
+                    "failed to create job trace file `{}`: {}",
+                    self.path.display(),
+                    err
+                );
+                return;
+            }
+        };
+
+        let writer = Mutex::new(BufWriter::new(file));
+        let frame = AtomicU64::new(0);
+
+        let Some(render_app) = job_sub_app_mut(app) else {
+            return;
+        };
+
+        render_app
+            .world_mut()
+            .get_resource_or_insert_with(JobSubmitHooks::default)
+            .add_pre_submit(move |_world, jobs, _command_buffers| {
+                let frame = frame.fetch_add(1, Ordering::Relaxed);
+                if jobs.is_empty() {
+                    return;
+                }
+
+                // flushed every frame, so the trace survives a crash
+                let mut writer = writer.lock().unwrap();
+                if let Err(err) =
+                    writeln!(writer, "{}", trace_line(frame, jobs)).and_then(|()| writer.flush())
+                {
+                    error!("failed to write job trace: {}", err);
+                }
+            });
+    }
+}
+
+fn trace_line(frame: u64, jobs: &[SubmittedJob]) -> String {
+    let jobs = jobs
+        .iter()
+        .map(|job| {
+            let main_entity = job
+                .main_entity
+                .map_or("null".to_string(), |entity| format!("\"{}\"", entity));
+            format!(
+                "{{\"label\":\"{}\",\"entity\":\"{}\",\"main_entity\":{}}}",
+                escape(&job.label.to_string()),
+                job.entity,
+                main_entity
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{\"frame\":{},\"jobs\":[{}]}}", frame, jobs)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,462 @@
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::system::{Res, ResMut, Resource};
+use bevy_render::{
+    render_resource::{
+        CachedComputePipelineId, CachedRenderPipelineId, PipelineCache, Shader, Source,
+    },
+    Extract,
+};
+use bevy_utils::{tracing::error, HashSet};
+use disqualified::ShortName;
+
+/// A pipeline queued for a graphics job
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum JobPipelineId {
+    Render(CachedRenderPipelineId),
+    Compute(CachedComputePipelineId),
+}
+
+/// Tracks job pipelines whose shader entry points should be validated, when
+/// [`JobExecutionSettings::validate_entry_points`](crate::JobExecutionSettings::validate_entry_points)
+/// is enabled.
+#[derive(Resource, Default)]
+pub(crate) struct EntryPointValidation {
+    pending: Vec<(JobPipelineId, ShortName<'static>)>,
+    checked: HashSet<JobPipelineId>,
+    invalid: HashSet<JobPipelineId>,
+}
+
+impl EntryPointValidation {
+    /// Queues a pipeline to be validated, if it hasn't been already. `label`
+    /// is used to attribute errors to the job pipeline type.
+    pub fn queue(&mut self, id: JobPipelineId, label: ShortName<'static>) {
+        if self.checked.insert(id) {
+            self.pending.push((id, label));
+        }
+    }
+
+    /// Returns true if the pipeline was found to be missing an entry point
+    pub fn is_invalid(&self, id: JobPipelineId) -> bool {
+        self.invalid.contains(&id)
+    }
+}
+
+pub(crate) fn validate_entry_points(
+    shaders: Extract<Res<Assets<Shader>>>,
+    pipeline_cache: Res<PipelineCache>,
+    mut validation: ResMut<EntryPointValidation>,
+) {
+    let EntryPointValidation {
+        pending, invalid, ..
+    } = &mut *validation;
+
+    pending.retain(|&(id, label)| {
+        let entry_points = pipeline_shaders(&pipeline_cache, id);
+
+        for (handle, entry_point) in entry_points {
+            let Some(shader) = shaders.get(handle) else {
+                // wait for the shader to load
+                return true;
+            };
+
+            let Source::Wgsl(source) = &shader.source else {
+                continue;
+            };
+
+            if !wgsl_has_entry_point(source, entry_point) {
+                error!(
+                    "{}: entry point `{}` was not found in shader `{}`",
+                    label, entry_point, shader.path
+                );
+                invalid.insert(id);
+            }
+        }
+
+        false
+    });
+}
+
+/// Returns the shaders of a pipeline, with the entry point used from each
+fn pipeline_shaders(
+    pipeline_cache: &PipelineCache,
+    id: JobPipelineId,
+) -> Vec<(&Handle<Shader>, &str)> {
+    match id {
+        JobPipelineId::Render(id) => {
+            let descriptor = pipeline_cache.get_render_pipeline_descriptor(id);
+            let vertex = &descriptor.vertex;
+            let fragment = descriptor.fragment.as_ref();
+            Some((&vertex.shader, &*vertex.entry_point))
+                .into_iter()
+                .chain(fragment.map(|fragment| (&fragment.shader, &*fragment.entry_point)))
+                .collect()
+        }
+        JobPipelineId::Compute(id) => {
+            let descriptor = pipeline_cache.get_compute_pipeline_descriptor(id);
+            vec![(&descriptor.shader, &*descriptor.entry_point)]
+        }
+    }
+}
+
+/// Checks whether a WGSL source contains a function named `entry_point`.
+/// This is a textual check, and doesn't account for comments.
+fn wgsl_has_entry_point(source: &str, entry_point: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    source.match_indices("fn").any(|(i, _)| {
+        let before = source[..i].chars().next_back();
+        if before.is_some_and(is_ident) {
+            return false;
+        }
+
+        let rest = &source[i + 2..];
+        let name = rest.trim_start();
+        if name.len() == rest.len() {
+            return false;
+        }
+
+        name.strip_prefix(entry_point)
+            .is_some_and(|after| after.trim_start().starts_with('('))
+    })
+}
+
+/// Validation of job bind group layouts against the shaders of job pipelines,
+/// enabled by the `validate-layouts` feature. `wgpu` only reports mismatches when
+/// the pipeline is used, with errors that don't mention the job, so this checks
+/// them as soon as both are known and logs an error naming the bind group and
+/// pipeline instead.
+///
+/// Like entry point validation, this is a textual check of the WGSL source, so
+/// bindings declared in imported modules, or behind shader defs, may be missed.
+#[cfg(feature = "validate-layouts")]
+pub(crate) mod layouts {
+    use core::any::TypeId;
+
+    use bevy_app::App;
+    use bevy_asset::Assets;
+    use bevy_ecs::{
+        component::Component,
+        query::With,
+        system::{Query, Res, ResMut, Resource},
+    };
+    use bevy_render::{
+        render_resource::{
+            AsBindGroup, BindGroupLayoutEntry, BindingType, BufferBindingType, PipelineCache,
+            Shader, Source,
+        },
+        renderer::RenderDevice,
+        Extract,
+    };
+    use bevy_utils::{tracing::error, HashMap, HashSet};
+    use disqualified::ShortName;
+
+    use super::{pipeline_shaders, JobPipelineId};
+    use crate::{input::PreparedJobBindGroup, job_sub_app_mut};
+
+    /// The pipeline used by a job, inserted alongside its pipeline id
+    #[derive(Component, Copy, Clone)]
+    pub(crate) struct JobLayoutPipeline(pub JobPipelineId, pub ShortName<'static>);
+
+    #[derive(Resource, Default)]
+    pub(crate) struct LayoutValidation {
+        /// The indices each bind group type is set at. Bind groups without
+        /// a known index may match any group in the shader.
+        indices: HashMap<TypeId, HashSet<u32>>,
+        pending: Vec<PendingLayout>,
+        checked: HashSet<(JobPipelineId, TypeId)>,
+    }
+
+    struct PendingLayout {
+        pipeline: JobPipelineId,
+        pipeline_label: ShortName<'static>,
+        bind_group_label: ShortName<'static>,
+        indices: Vec<u32>,
+        entries: Vec<BindGroupLayoutEntry>,
+    }
+
+    /// Records that the bind group `B` is set at `index`
+    pub(crate) fn register_bind_group_index<B: 'static>(app: &mut App, index: u32) {
+        if let Some(render_app) = job_sub_app_mut(app) {
+            render_app
+                .world_mut()
+                .get_resource_or_insert_with(LayoutValidation::default)
+                .indices
+                .entry(TypeId::of::<B>())
+                .or_default()
+                .insert(index);
+        }
+    }
+
+    pub(crate) fn queue_bind_group_layout_validation<B: Component + AsBindGroup>(
+        jobs: Query<&JobLayoutPipeline, With<PreparedJobBindGroup<B>>>,
+        render_device: Res<RenderDevice>,
+        mut validation: ResMut<LayoutValidation>,
+    ) {
+        for &JobLayoutPipeline(pipeline, pipeline_label) in &jobs {
+            if !validation.checked.insert((pipeline, TypeId::of::<B>())) {
+                continue;
+            }
+
+            let indices = validation
+                .indices
+                .get(&TypeId::of::<B>())
+                .map(|indices| indices.iter().copied().collect())
+                .unwrap_or_default();
+            validation.pending.push(PendingLayout {
+                pipeline,
+                pipeline_label,
+                bind_group_label: ShortName::of::<B>(),
+                indices,
+                entries: B::bind_group_layout_entries(&render_device),
+            });
+        }
+    }
+
+    pub(crate) fn validate_bind_group_layouts(
+        shaders: Extract<Res<Assets<Shader>>>,
+        pipeline_cache: Res<PipelineCache>,
+        mut validation: ResMut<LayoutValidation>,
+    ) {
+        validation.pending.retain(|layout| {
+            let mut bindings = Vec::new();
+            for (handle, _) in pipeline_shaders(&pipeline_cache, layout.pipeline) {
+                let Some(shader) = shaders.get(handle) else {
+                    // wait for the shader to load
+                    return true;
+                };
+                if let Source::Wgsl(source) = &shader.source {
+                    bindings.extend(
+                        wgsl_bindings(source)
+                            .into_iter()
+                            .map(|binding| (binding, &shader.path)),
+                    );
+                }
+            }
+
+            let groups: Vec<u32> = if layout.indices.is_empty() {
+                let mut groups: Vec<u32> =
+                    bindings.iter().map(|(binding, _)| binding.group).collect();
+                groups.sort_unstable();
+                groups.dedup();
+                groups
+            } else {
+                layout.indices.clone()
+            };
+
+            let mismatches: Vec<Vec<String>> = groups
+                .iter()
+                .map(|&group| {
+                    bindings
+                        .iter()
+                        .filter(|(binding, _)| binding.group == group)
+                        .filter_map(|(binding, path)| {
+                            binding_mismatch(binding, &layout.entries)
+                                .map(|mismatch| format!("{mismatch}, in shader `{path}`"))
+                        })
+                        .collect()
+                })
+                .collect();
+
+            if mismatches.iter().all(|mismatches| !mismatches.is_empty()) {
+                for mismatch in mismatches.into_iter().flatten() {
+                    error!(
+                        "{}: bind group `{}` doesn't match the pipeline: {}",
+                        layout.pipeline_label, layout.bind_group_label, mismatch
+                    );
+                }
+            }
+
+            false
+        });
+    }
+
+    /// The kind of resource a binding holds
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    pub(super) enum BindingKind {
+        UniformBuffer,
+        StorageBuffer { read_only: bool },
+        Sampler,
+        Texture,
+        StorageTexture,
+        Other,
+    }
+
+    impl BindingKind {
+        fn of_entry(ty: &BindingType) -> Self {
+            match ty {
+                BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    ..
+                } => Self::UniformBuffer,
+                BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only },
+                    ..
+                } => Self::StorageBuffer {
+                    read_only: *read_only,
+                },
+                BindingType::Sampler(_) => Self::Sampler,
+                BindingType::Texture { .. } => Self::Texture,
+                BindingType::StorageTexture { .. } => Self::StorageTexture,
+                _ => Self::Other,
+            }
+        }
+
+        /// Returns true if a layout entry of kind `self` may be bound to a shader binding
+        /// of kind `shader`. Read-write storage buffers may be bound to read-only bindings.
+        fn is_compatible_with(self, shader: Self) -> bool {
+            match (self, shader) {
+                (Self::Other, _) | (_, Self::Other) => true,
+                (
+                    Self::StorageBuffer { read_only },
+                    Self::StorageBuffer {
+                        read_only: shader_read_only,
+                    },
+                ) => !read_only || shader_read_only,
+                (layout, shader) => layout == shader,
+            }
+        }
+    }
+
+    /// A binding declared in a shader
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    pub(super) struct WgslBinding {
+        pub group: u32,
+        pub binding: u32,
+        pub kind: BindingKind,
+    }
+
+    /// Describes how a shader binding doesn't match a bind group layout, if it doesn't
+    fn binding_mismatch(binding: &WgslBinding, entries: &[BindGroupLayoutEntry]) -> Option<String> {
+        let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.binding == binding.binding)
+        else {
+            return Some(format!(
+                "binding {} of group {} is missing from the layout",
+                binding.binding, binding.group
+            ));
+        };
+
+        let layout_kind = BindingKind::of_entry(&entry.ty);
+        (!layout_kind.is_compatible_with(binding.kind)).then(|| {
+            format!(
+                "binding {} of group {} is a {:?} in the shader, but a {:?} in the layout",
+                binding.binding, binding.group, binding.kind, layout_kind
+            )
+        })
+    }
+
+    /// Finds the resource bindings declared in a WGSL source
+    pub(super) fn wgsl_bindings(source: &str) -> Vec<WgslBinding> {
+        source
+            .split(';')
+            .filter_map(|declaration| {
+                // skip any function bodies before the declaration
+                let declaration = match declaration.rfind('}') {
+                    Some(i) => &declaration[i + 1..],
+                    None => declaration,
+                };
+                let group = attribute_value(declaration, "@group")?;
+                let binding = attribute_value(declaration, "@binding")?;
+                let (_, var) = declaration.split_once("var")?;
+                Some(WgslBinding {
+                    group,
+                    binding,
+                    kind: var_kind(var),
+                })
+            })
+            .collect()
+    }
+
+    fn attribute_value(declaration: &str, attribute: &str) -> Option<u32> {
+        let (_, rest) = declaration.split_once(attribute)?;
+        let (value, _) = rest.trim_start().strip_prefix('(')?.split_once(')')?;
+        value.trim().parse().ok()
+    }
+
+    /// Returns the kind of a `var` declaration, given the text following `var`
+    fn var_kind(var: &str) -> BindingKind {
+        if let Some(rest) = var.trim_start().strip_prefix('<') {
+            let address_space = rest.split('>').next().unwrap_or_default();
+            let mut parts = address_space.split(',').map(str::trim);
+            return match parts.next() {
+                Some("uniform") => BindingKind::UniformBuffer,
+                Some("storage") => BindingKind::StorageBuffer {
+                    read_only: parts.next() != Some("read_write"),
+                },
+                _ => BindingKind::Other,
+            };
+        }
+
+        let ty = var.split_once(':').map_or("", |(_, ty)| ty.trim_start());
+        if ty.starts_with("texture_storage") {
+            BindingKind::StorageTexture
+        } else if ty.starts_with("texture_") {
+            BindingKind::Texture
+        } else if ty.starts_with("sampler") {
+            BindingKind::Sampler
+        } else {
+            BindingKind::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::wgsl_has_entry_point;
+
+    const SOURCE: &str = "
+        fn helper(x: f32) -> f32 { return x; }
+
+        @compute @workgroup_size(16, 16, 1)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {}
+
+        @compute @workgroup_size(64)
+        fn main_2 (@builtin(global_invocation_id) id: vec3<u32>) {}
+    ";
+
+    #[test]
+    fn finds_entry_points() {
+        assert!(wgsl_has_entry_point(SOURCE, "main"));
+        assert!(wgsl_has_entry_point(SOURCE, "main_2"));
+        assert!(wgsl_has_entry_point(SOURCE, "helper"));
+    }
+
+    #[test]
+    #[cfg(feature = "validate-layouts")]
+    fn finds_bindings() {
+        use super::layouts::{wgsl_bindings, BindingKind, WgslBinding};
+
+        let source = "
+            @group(0) @binding(0) var<uniform> params: Params;
+            @group(0) @binding(1) var<storage, read_write> data: array<f32>;
+            @binding(0) @group(1) var image: texture_storage_2d<rgba8unorm, write>;
+
+            fn helper(x: f32) -> f32 { return x; }
+
+            @group(1) @binding(1) var image_sampler: sampler;
+        ";
+
+        let binding = |group, binding, kind| WgslBinding {
+            group,
+            binding,
+            kind,
+        };
+        assert_eq!(
+            wgsl_bindings(source),
+            vec![
+                binding(0, 0, BindingKind::UniformBuffer),
+                binding(0, 1, BindingKind::StorageBuffer { read_only: false }),
+                binding(1, 0, BindingKind::StorageTexture),
+                binding(1, 1, BindingKind::Sampler),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_entry_points() {
+        assert!(!wgsl_has_entry_point(SOURCE, "mian"));
+        assert!(!wgsl_has_entry_point(SOURCE, "mai"));
+        assert!(!wgsl_has_entry_point(SOURCE, "x"));
+    }
+}